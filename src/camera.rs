@@ -0,0 +1,173 @@
+use crate::matrix::Matrix4;
+use crate::ray::Ray;
+use crate::tuple::Tuple;
+use crate::world::World;
+use crate::canvas::Canvas;
+
+// the pixel size and half extents are derived in new, so the sizes and field of view are read-only
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub struct Camera {
+    hsize: usize,
+    vsize: usize,
+    field_of_view: f64,
+    pub(crate) transform: Matrix4,
+    pixel_size: f64,
+    half_width: f64,
+    half_height: f64,
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f64 / vsize as f64;
+
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+
+        return Camera {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Matrix4::identity(),
+            pixel_size: (half_width * 2.0) / hsize as f64,
+            half_width,
+            half_height,
+        };
+    }
+
+    #[allow(dead_code)]
+    pub fn hsize(&self) -> usize {
+        return self.hsize;
+    }
+
+    #[allow(dead_code)]
+    pub fn vsize(&self) -> usize {
+        return self.vsize;
+    }
+
+    #[allow(dead_code)]
+    pub fn field_of_view(&self) -> f64 {
+        return self.field_of_view;
+    }
+
+    pub fn set_transform(&mut self, new_transform: Matrix4) {
+        self.transform = new_transform;
+    }
+
+    pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
+        // offset from the edge of the canvas to the pixel's center
+        let xoffset = (px as f64 + 0.5) * self.pixel_size;
+        let yoffset = (py as f64 + 0.5) * self.pixel_size;
+
+        // the camera looks toward -z, so +x is to the *left*
+        let world_x = self.half_width - xoffset;
+        let world_y = self.half_height - yoffset;
+
+        // the canvas is at z = -1
+        let inv_transform = self.transform.inverse();
+        let pixel = inv_transform * Tuple::point(world_x, world_y, -1.0);
+        let origin = inv_transform * Tuple::point(0.0, 0.0, 0.0);
+        let direction = (pixel - origin).normalize();
+
+        return Ray { origin, direction };
+    }
+
+    pub fn render(&self, world: &World) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let ray = self.ray_for_pixel(x, y);
+                let color = world.color_at(&ray);
+                image.write_pixel(x, y, color);
+            }
+        }
+
+        return image;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use crate::camera::Camera;
+    use crate::matrix::Matrix4;
+    use crate::tuple::Tuple;
+    use crate::world::World;
+    use crate::color::Color;
+
+    #[test]
+    fn constructing_a_camera() {
+        let c = Camera::new(160, 120, PI / 2.0);
+
+        assert_eq!(160, c.hsize());
+        assert_eq!(120, c.vsize());
+        assert_eq!(PI / 2.0, c.field_of_view());
+        assert_eq!(Matrix4::identity(), c.transform);
+    }
+
+    #[test]
+    fn the_pixel_size_for_a_horizontal_canvas() {
+        let c = Camera::new(200, 125, PI / 2.0);
+
+        assert_eq!(0.01, (c.pixel_size * 100000.0).round() / 100000.0);
+    }
+
+    #[test]
+    fn the_pixel_size_for_a_vertical_canvas() {
+        let c = Camera::new(125, 200, PI / 2.0);
+
+        assert_eq!(0.01, (c.pixel_size * 100000.0).round() / 100000.0);
+    }
+
+    #[test]
+    fn constructing_a_ray_through_the_center_of_the_canvas() {
+        let c = Camera::new(201, 101, PI / 2.0);
+
+        let r = c.ray_for_pixel(100, 50);
+
+        assert_eq!(Tuple::point(0.0, 0.0, 0.0), r.origin.round());
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), r.direction.round());
+    }
+
+    #[test]
+    fn constructing_a_ray_through_a_corner_of_the_canvas() {
+        let c = Camera::new(201, 101, PI / 2.0);
+
+        let r = c.ray_for_pixel(0, 0);
+
+        assert_eq!(Tuple::point(0.0, 0.0, 0.0), r.origin.round());
+        assert_eq!(Tuple::vector(0.66519, 0.33259, -0.66851), r.direction.round());
+    }
+
+    #[test]
+    fn constructing_a_ray_when_the_camera_is_transformed() {
+        let mut c = Camera::new(201, 101, PI / 2.0);
+        c.set_transform(Matrix4::rotation_y(PI / 4.0) * Matrix4::translation(0.0, -2.0, 5.0));
+
+        let r = c.ray_for_pixel(100, 50);
+
+        assert_eq!(Tuple::point(0.0, 2.0, -5.0), r.origin.round());
+        assert_eq!(Tuple::vector(2.0_f64.sqrt() / 2.0, 0.0, -2.0_f64.sqrt() / 2.0).round(), r.direction.round());
+    }
+
+    #[test]
+    fn rendering_a_world_with_a_camera() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.0);
+        let from = Tuple::point(0.0, 0.0, -5.0);
+        let to = Tuple::point(0.0, 0.0, 0.0);
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+        c.set_transform(Matrix4::view_transform(from, to, up));
+
+        let image = c.render(&w);
+
+        assert_eq!(11, image.width());
+        assert_eq!(11, image.height());
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), image.pixel_at(5, 5).round());
+    }
+}
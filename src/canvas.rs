@@ -1,61 +1,60 @@
 use crate::color::Color;
 use ndarray::Array2;
+use crate::ppm::PpmFormat;
 
+// alpha is kept as a separate coverage plane, 1 is opaque and 0 fully transparent
 #[derive(Debug)]
 #[derive(PartialEq)]
 pub struct Canvas {
     pixels: Array2::<Color>,
+    alpha: Array2::<f64>,
 }
 
 impl Canvas {
+    // the whole image as a plain 8-bit PPM
+    #[allow(dead_code)]
     pub(crate) fn to_ppm(&self) -> String {
-        let header = format!("P3\n{} {}\n255\n", self.width().to_string(), self.height().to_string());
-        let mut res = String::from(header);
-
-        for h in 0..self.height() {
-            let mut line_counter: usize = 0;
-            let line_start_counter  = res.len();
-            for w in 0..self.width() {
-                let red = (self.pixels[[w, h]].red * 256.0) as u8;
-                let green = (self.pixels[[w, h]].green * 256.0) as u8;
-                let blue = (self.pixels[[w, h]].blue * 256.0) as u8;
-
-                line_counter = Canvas::add_color_to_file(&mut res, line_counter, red, line_start_counter);
-                line_counter = Canvas::add_color_to_file(&mut res, line_counter, green, line_start_counter);
-                line_counter = Canvas::add_color_to_file(&mut res, line_counter, blue, line_start_counter);
-            }
-            res.push_str("\n");
-        }
+        let mut ppm = Vec::new();
+        self.write_ppm(&mut ppm, PpmFormat::Plain, 255).expect("writing to memory cannot fail");
 
-        return res;
+        return String::from_utf8(ppm).expect("plain PPM is ASCII");
     }
 
-    fn add_color_to_file(res: &mut String, line_counter: usize, color: u8, line_start_counter: usize) -> usize {
-        let mut local_counter = line_counter;
-        let color_string = color.to_string();
+    pub(crate) fn write_pixel(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[[x, y]] = color;
+    }
 
-        if color_string.len() + local_counter >= 70 {
-            res.push_str(&format!("\n{}", color_string));
-            local_counter = color_string.len();
-        }
-        else if line_start_counter == res.len() {// Missing test
-            res.push_str( &color_string);
-            local_counter = color_string.len();
-        }
-        else {
-            res.push_str(&format!(" {}", color_string));
-            local_counter = local_counter + 1 + color_string.len();
-        }
+    #[allow(dead_code)]
+    pub fn pixel_at(&self, x: usize, y: usize) -> Color {
+        return self.pixels[[x, y]];
+    }
 
-        return local_counter;
+    pub(crate) fn write_alpha(&mut self, x: usize, y: usize, alpha: f64) {
+        self.alpha[[x, y]] = alpha;
     }
 
-    pub(crate) fn write_pixel(&mut self, x: usize, y: usize, color: Color) {
-        self.pixels[[x, y]] = color;
+    pub fn alpha_at(&self, x: usize, y: usize) -> f64 {
+        return self.alpha[[x, y]];
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        return self.alpha.iter().all(|alpha| *alpha >= 1.0);
     }
 
+    // an opaque black canvas
     pub fn new(width: usize, height: usize) -> Self {
-        Canvas { pixels: Array2::<Color>::default((width, height)) }
+        Canvas {
+            pixels: Array2::<Color>::default((width, height)),
+            alpha: Array2::<f64>::from_elem((width, height), 1.0),
+        }
+    }
+
+    // a canvas to draw layers on, nothing shows through where nothing was drawn
+    pub fn transparent(width: usize, height: usize) -> Self {
+        Canvas {
+            pixels: Array2::<Color>::default((width, height)),
+            alpha: Array2::<f64>::zeros((width, height)),
+        }
     }
 
     pub fn width(&self) -> usize {
@@ -69,10 +68,21 @@ impl Canvas {
 
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use crate::color::Color;
     use crate::canvas::Canvas;
 
+    // a 2x2 canvas with values below zero, above one and in between, for the image file tests
+    pub fn test_canvas() -> Canvas {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 0.5));
+        c.write_pixel(1, 0, Color::new(0.0, 1.5, 0.0));
+        c.write_pixel(0, 1, Color::new(-0.5, 0.0, 1.0));
+        c.write_pixel(1, 1, Color::new(0.2, 0.4, 0.6));
+
+        return c;
+    }
+
     #[test]
     fn creating_a_canvas() {
         let c = Canvas::new(10, 20);
@@ -87,6 +97,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn a_new_canvas_is_opaque() {
+        let c = Canvas::new(3, 2);
+
+        assert_eq!(1.0, c.alpha_at(2, 1));
+        assert!(c.is_opaque());
+    }
+
+    #[test]
+    fn a_transparent_canvas_has_no_coverage() {
+        let c = Canvas::transparent(3, 2);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), c.pixel_at(1, 1));
+        assert_eq!(0.0, c.alpha_at(1, 1));
+        assert!(!c.is_opaque());
+    }
+
+    #[test]
+    fn writing_alpha_to_a_canvas() {
+        let mut c = Canvas::new(3, 2);
+
+        c.write_alpha(1, 0, 0.25);
+
+        assert_eq!(0.25, c.alpha_at(1, 0));
+        assert!(!c.is_opaque());
+    }
+
     #[test]
     fn writing_pixels_to_a_canvas() {
         let mut c = Canvas::new(10, 20);
@@ -0,0 +1,404 @@
+// image operations on finished canvases, the renderer itself doesn't call them
+#![cfg_attr(not(test), allow(dead_code))]
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+// how resize picks colors between source pixels
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum ResizeFilter {
+    Nearest,
+    Bilinear,
+}
+
+// Porter-Duff "over" on straight (not premultiplied) colors, returns the combined color and alpha
+pub fn over(source: Color, source_alpha: f64, destination: Color, destination_alpha: f64) -> (Color, f64) {
+    let alpha = source_alpha + destination_alpha * (1.0 - source_alpha);
+    if alpha <= 0.0 {
+        return (Color::new(0.0, 0.0, 0.0), 0.0);
+    }
+
+    let color = (source * source_alpha + destination * (destination_alpha * (1.0 - source_alpha))) * (1.0 / alpha);
+    return (color, alpha);
+}
+
+// the source coordinate whose pixel center lines up with the target pixel's center
+fn source_coordinate(target: usize, target_size: usize, source_size: usize) -> f64 {
+    return (target as f64 + 0.5) * source_size as f64 / target_size as f64 - 0.5;
+}
+
+impl Canvas {
+    fn from_fn(width: usize, height: usize, pixel: impl Fn(usize, usize) -> (Color, f64)) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let (color, alpha) = pixel(x, y);
+                canvas.write_pixel(x, y, color);
+                canvas.write_alpha(x, y, alpha);
+            }
+        }
+
+        return canvas;
+    }
+
+    fn pixel_and_alpha(&self, x: usize, y: usize) -> (Color, f64) {
+        return (self.pixel_at(x, y), self.alpha_at(x, y));
+    }
+
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Result<Canvas, String> {
+        let fits = |start: usize, length: usize, limit: usize| start.checked_add(length).is_some_and(|end| end <= limit);
+        if !fits(x, width, self.width()) || !fits(y, height, self.height()) {
+            return Err(format!("crop region {}x{} at ({}, {}) does not fit in the {}x{} canvas",
+                               width, height, x, y, self.width(), self.height()));
+        }
+
+        return Ok(Canvas::from_fn(width, height, |cx, cy| self.pixel_and_alpha(x + cx, y + cy)));
+    }
+
+    // resizing an empty canvas gives a transparent one, there is nothing to sample
+    pub fn resize(&self, width: usize, height: usize, filter: ResizeFilter) -> Canvas {
+        if self.width() == 0 || self.height() == 0 {
+            return Canvas::transparent(width, height);
+        }
+
+        return match filter {
+            ResizeFilter::Nearest => Canvas::from_fn(width, height, |x, y| {
+                let nearest = |target, target_size, source_size| {
+                    let source = (source_coordinate(target, target_size, source_size) + 0.5).floor().max(0.0) as usize;
+                    return source.min(source_size - 1);
+                };
+                return self.pixel_and_alpha(nearest(x, width, self.width()), nearest(y, height, self.height()));
+            }),
+            ResizeFilter::Bilinear => Canvas::from_fn(width, height, |x, y| {
+                self.bilinear_sample(source_coordinate(x, width, self.width()), source_coordinate(y, height, self.height()))
+            }),
+        };
+    }
+
+    // colors are weighted by their alpha so transparent pixels do not bleed their color into the result
+    fn bilinear_sample(&self, sx: f64, sy: f64) -> (Color, f64) {
+        let clamp = |value: f64, size: usize| value.max(0.0).min((size - 1) as f64) as usize;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (tx, ty) = (sx - x0, sy - y0);
+
+        let mut color = Color::new(0.0, 0.0, 0.0);
+        let mut alpha = 0.0;
+        for (px, py, weight) in [
+            (x0, y0, (1.0 - tx) * (1.0 - ty)),
+            (x0 + 1.0, y0, tx * (1.0 - ty)),
+            (x0, y0 + 1.0, (1.0 - tx) * ty),
+            (x0 + 1.0, y0 + 1.0, tx * ty),
+        ].iter() {
+            let (x, y) = (clamp(*px, self.width()), clamp(*py, self.height()));
+            let a = self.alpha_at(x, y) * weight;
+            color = color + self.pixel_at(x, y) * a;
+            alpha += a;
+        }
+
+        if alpha <= 0.0 {
+            return (Color::new(0.0, 0.0, 0.0), 0.0);
+        }
+        return (color * (1.0 / alpha), alpha);
+    }
+
+    // mirrors left and right
+    pub fn flip_horizontal(&self) -> Canvas {
+        return Canvas::from_fn(self.width(), self.height(), |x, y| self.pixel_and_alpha(self.width() - 1 - x, y));
+    }
+
+    // mirrors top and bottom
+    pub fn flip_vertical(&self) -> Canvas {
+        return Canvas::from_fn(self.width(), self.height(), |x, y| self.pixel_and_alpha(x, self.height() - 1 - y));
+    }
+
+    // a quarter turn, the left column becomes the top row
+    pub fn rotate_clockwise(&self) -> Canvas {
+        return Canvas::from_fn(self.height(), self.width(), |x, y| self.pixel_and_alpha(y, self.height() - 1 - x));
+    }
+
+    // a quarter turn, the top row becomes the left column
+    pub fn rotate_counter_clockwise(&self) -> Canvas {
+        return Canvas::from_fn(self.height(), self.width(), |x, y| self.pixel_and_alpha(self.width() - 1 - y, x));
+    }
+
+    // calls f with each source pixel and the target pixel it lands on, skipping the ones outside this canvas
+    fn for_each_overlap(&mut self, source: &Canvas, x: isize, y: isize, mut f: impl FnMut(&mut Canvas, usize, usize, usize, usize)) {
+        for sy in 0..source.height() {
+            let ty = y + sy as isize;
+            if ty < 0 || ty >= self.height() as isize {
+                continue;
+            }
+            for sx in 0..source.width() {
+                let tx = x + sx as isize;
+                if tx < 0 || tx >= self.width() as isize {
+                    continue;
+                }
+                f(self, sx, sy, tx as usize, ty as usize);
+            }
+        }
+    }
+
+    // copies source with its top left corner at (x, y), replacing color and alpha, clipped to this canvas
+    pub fn blit(&mut self, source: &Canvas, x: isize, y: isize) {
+        self.for_each_overlap(source, x, y, |target, sx, sy, tx, ty| {
+            target.write_pixel(tx, ty, source.pixel_at(sx, sy));
+            target.write_alpha(tx, ty, source.alpha_at(sx, sy));
+        });
+    }
+
+    // lays layer over this canvas with its top left corner at (x, y), clipped to this canvas
+    pub fn composite(&mut self, layer: &Canvas, x: isize, y: isize) {
+        self.for_each_overlap(layer, x, y, |target, sx, sy, tx, ty| {
+            let (color, alpha) = over(layer.pixel_at(sx, sy), layer.alpha_at(sx, sy), target.pixel_at(tx, ty), target.alpha_at(tx, ty));
+            target.write_pixel(tx, ty, color);
+            target.write_alpha(tx, ty, alpha);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas_operations::{ResizeFilter, over};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+
+    // every pixel's color holds its own coordinates, which makes moved pixels easy to follow
+    fn numbered(width: usize, height: usize) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                c.write_pixel(x, y, Color::new(x as f64, y as f64, 0.0));
+            }
+        }
+
+        return c;
+    }
+
+    fn position(c: &Canvas, x: usize, y: usize) -> (f64, f64) {
+        let color = c.pixel_at(x, y);
+        return (color.red, color.green);
+    }
+
+    #[test]
+    fn cropping_a_canvas() {
+        let c = numbered(4, 3);
+
+        let cropped = c.crop(1, 1, 2, 2).unwrap();
+
+        assert_eq!((2, 2), (cropped.width(), cropped.height()));
+        assert_eq!((1.0, 1.0), position(&cropped, 0, 0));
+        assert_eq!((2.0, 2.0), position(&cropped, 1, 1));
+    }
+
+    #[test]
+    fn cropping_keeps_the_alpha() {
+        let mut c = numbered(4, 3);
+        c.write_alpha(3, 2, 0.5);
+
+        let cropped = c.crop(2, 1, 2, 2).unwrap();
+
+        assert_eq!(0.5, cropped.alpha_at(1, 1));
+    }
+
+    #[test]
+    fn cropping_outside_the_canvas_fails() {
+        let c = numbered(4, 3);
+
+        assert_eq!(Err("crop region 2x2 at (3, 0) does not fit in the 4x3 canvas".to_string()), c.crop(3, 0, 2, 2));
+    }
+
+    #[test]
+    fn cropping_far_outside_the_canvas_fails() {
+        let c = numbered(4, 3);
+
+        assert_eq!(Err(format!("crop region 1x1 at ({}, 0) does not fit in the 4x3 canvas", usize::MAX)), c.crop(usize::MAX, 0, 1, 1));
+        assert_eq!(Err(format!("crop region 1x{} at (0, 1) does not fit in the 4x3 canvas", usize::MAX)), c.crop(0, 1, 1, usize::MAX));
+    }
+
+    #[test]
+    fn nearest_upscaling_repeats_pixels() {
+        let c = numbered(2, 2);
+
+        let resized = c.resize(4, 4, ResizeFilter::Nearest);
+
+        assert_eq!((4, 4), (resized.width(), resized.height()));
+        assert_eq!((0.0, 0.0), position(&resized, 1, 1));
+        assert_eq!((1.0, 0.0), position(&resized, 2, 0));
+        assert_eq!((1.0, 1.0), position(&resized, 3, 3));
+    }
+
+    #[test]
+    fn nearest_downscaling_picks_pixels() {
+        let c = numbered(4, 4);
+
+        let resized = c.resize(2, 1, ResizeFilter::Nearest);
+
+        // halfway between two source pixels the later one wins
+        assert_eq!((1.0, 2.0), position(&resized, 0, 0));
+        assert_eq!((3.0, 2.0), position(&resized, 1, 0));
+    }
+
+    #[test]
+    fn bilinear_resizing_interpolates_between_pixel_centers() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(1, 0, Color::new(1.0, 1.0, 1.0));
+
+        let resized = c.resize(4, 1, ResizeFilter::Bilinear);
+
+        let reds: Vec<f64> = (0..4).map(|x| resized.pixel_at(x, 0).red).collect();
+        assert_eq!(vec!(0.0, 0.25, 0.75, 1.0), reds);
+    }
+
+    #[test]
+    fn bilinear_resizing_to_the_same_size_changes_nothing() {
+        let c = numbered(3, 2);
+
+        let resized = c.resize(3, 2, ResizeFilter::Bilinear);
+
+        assert_eq!(c, resized);
+    }
+
+    #[test]
+    fn bilinear_resizing_weights_colors_by_alpha() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        c.write_alpha(1, 0, 0.0);
+
+        let resized = c.resize(1, 1, ResizeFilter::Bilinear);
+
+        assert_eq!(Color::new(1.0, 0.0, 0.0), resized.pixel_at(0, 0));
+        assert_eq!(0.5, resized.alpha_at(0, 0));
+    }
+
+    #[test]
+    fn resizing_an_empty_canvas_gives_a_transparent_one() {
+        let resized = Canvas::new(0, 0).resize(2, 2, ResizeFilter::Bilinear);
+
+        assert_eq!(Canvas::transparent(2, 2), resized);
+    }
+
+    #[test]
+    fn flipping_horizontally() {
+        let flipped = numbered(3, 2).flip_horizontal();
+
+        assert_eq!((2.0, 0.0), position(&flipped, 0, 0));
+        assert_eq!((0.0, 1.0), position(&flipped, 2, 1));
+    }
+
+    #[test]
+    fn flipping_vertically() {
+        let flipped = numbered(3, 2).flip_vertical();
+
+        assert_eq!((0.0, 1.0), position(&flipped, 0, 0));
+        assert_eq!((2.0, 0.0), position(&flipped, 2, 1));
+    }
+
+    #[test]
+    fn rotating_clockwise() {
+        let rotated = numbered(3, 2).rotate_clockwise();
+
+        assert_eq!((2, 3), (rotated.width(), rotated.height()));
+        // the bottom left corner moves to the top left
+        assert_eq!((0.0, 1.0), position(&rotated, 0, 0));
+        assert_eq!((0.0, 0.0), position(&rotated, 1, 0));
+        assert_eq!((2.0, 0.0), position(&rotated, 1, 2));
+    }
+
+    #[test]
+    fn rotating_counter_clockwise() {
+        let rotated = numbered(3, 2).rotate_counter_clockwise();
+
+        assert_eq!((2, 3), (rotated.width(), rotated.height()));
+        // the top right corner moves to the top left
+        assert_eq!((2.0, 0.0), position(&rotated, 0, 0));
+        assert_eq!((0.0, 1.0), position(&rotated, 1, 2));
+    }
+
+    #[test]
+    fn four_quarter_turns_make_a_full_turn() {
+        let c = numbered(3, 2);
+
+        let rotated = c.rotate_clockwise().rotate_clockwise().rotate_clockwise().rotate_clockwise();
+
+        assert_eq!(c, rotated);
+        assert_eq!(c, c.rotate_clockwise().rotate_counter_clockwise());
+        assert_eq!(c.flip_horizontal().flip_vertical(), c.rotate_clockwise().rotate_clockwise());
+    }
+
+    #[test]
+    fn blitting_replaces_pixels_and_alpha() {
+        let mut c = Canvas::new(3, 3);
+        let mut source = Canvas::transparent(1, 1);
+        source.write_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+
+        c.blit(&source, 1, 2);
+
+        assert_eq!(Color::new(1.0, 1.0, 1.0), c.pixel_at(1, 2));
+        assert_eq!(0.0, c.alpha_at(1, 2));
+        assert_eq!(1.0, c.alpha_at(1, 1));
+    }
+
+    #[test]
+    fn blitting_is_clipped_to_the_canvas() {
+        let mut c = Canvas::new(3, 3);
+
+        c.blit(&numbered(3, 3), -2, 1);
+
+        assert_eq!((2.0, 0.0), position(&c, 0, 1));
+        assert_eq!((2.0, 1.0), position(&c, 0, 2));
+        assert_eq!((0.0, 0.0), position(&c, 1, 1));
+    }
+
+    macro_rules! over_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (source, source_alpha, destination, destination_alpha, expected) = $value;
+
+                let (color, alpha) = over(source, source_alpha, destination, destination_alpha);
+
+                assert_eq!(expected, (color.round(), alpha));
+            }
+        )*
+        }
+    }
+
+    over_tests! {
+        over_an_opaque_source_hides_the_destination: (Color::new(1.0, 0.0, 0.0), 1.0, Color::new(0.0, 1.0, 0.0), 1.0, (Color::new(1.0, 0.0, 0.0), 1.0)),
+        over_a_transparent_source_shows_the_destination: (Color::new(1.0, 0.0, 0.0), 0.0, Color::new(0.0, 1.0, 0.0), 1.0, (Color::new(0.0, 1.0, 0.0), 1.0)),
+        over_half_coverage_blends: (Color::new(1.0, 0.0, 0.0), 0.5, Color::new(0.0, 1.0, 0.0), 1.0, (Color::new(0.5, 0.5, 0.0), 1.0)),
+        over_a_transparent_destination_keeps_the_source_color: (Color::new(1.0, 0.0, 0.0), 0.5, Color::new(0.0, 1.0, 0.0), 0.0, (Color::new(1.0, 0.0, 0.0), 0.5)),
+        over_two_half_covered_layers: (Color::new(1.0, 0.0, 0.0), 0.5, Color::new(0.0, 1.0, 0.0), 0.5, (Color::new(0.66667, 0.33333, 0.0), 0.75)),
+        over_nothing_on_nothing: (Color::new(1.0, 0.0, 0.0), 0.0, Color::new(0.0, 1.0, 0.0), 0.0, (Color::new(0.0, 0.0, 0.0), 0.0)),
+    }
+
+    #[test]
+    fn compositing_a_layer_over_a_canvas() {
+        let mut background = Canvas::new(2, 2);
+        let mut layer = Canvas::transparent(2, 1);
+        layer.write_pixel(1, 0, Color::new(1.0, 1.0, 1.0));
+        layer.write_alpha(1, 0, 0.25);
+
+        background.composite(&layer, 0, 1);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), background.pixel_at(0, 1));
+        assert_eq!(Color::new(0.25, 0.25, 0.25), background.pixel_at(1, 1));
+        assert!(background.is_opaque());
+    }
+
+    #[test]
+    fn compositing_is_clipped_to_the_canvas() {
+        let mut background = Canvas::new(2, 2);
+        let mut layer = Canvas::new(2, 2);
+        layer.write_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+
+        background.composite(&layer, 1, 1);
+        background.composite(&layer, 5, -5);
+
+        assert_eq!(Color::new(1.0, 1.0, 1.0), background.pixel_at(1, 1));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), background.pixel_at(0, 0));
+    }
+}
@@ -9,6 +9,21 @@ pub struct Color {
     pub blue: f64,
 }
 
+impl Color {
+    pub fn new(red: f64, green: f64, blue: f64) -> Self {
+        return Color { red, green, blue };
+    }
+
+    #[allow(dead_code)]
+    pub fn round(&self) -> Color {
+        return Color {
+            red: (self.red * 100000.0).round() / 100000.0,
+            green: (self.green * 100000.0).round() / 100000.0,
+            blue: (self.blue * 100000.0).round() / 100000.0,
+        };
+    }
+}
+
 impl Default for Color{
     fn default() -> Self {
         Color{red:0.0, green: 0.0, blue: 0.0}
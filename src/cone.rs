@@ -0,0 +1,241 @@
+use crate::matrix::Matrix4;
+use crate::tuple::{Tuple, EPSILON};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+use crate::shape_properties;
+
+// a double-napped cone around the y axis with its tip at the origin, optionally truncated and capped
+#[allow(dead_code)]
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Cone {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cone {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        return Cone {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        };
+    }
+
+    // checks if the intersection at t is within the cone's radius at the cap's height
+    fn check_cap(ray: &Ray, t: f64, radius: f64) -> bool {
+        let x = ray.origin.x + t * ray.direction.x;
+        let z = ray.origin.z + t * ray.direction.z;
+
+        return (x * x + z * z) <= radius * radius;
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Intersections<'a>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+
+        let t = (self.minimum - ray.origin.y) / ray.direction.y;
+        if Cone::check_cap(ray, t, self.minimum) {
+            xs.values.push(Intersection::new(t, self));
+        }
+
+        let t = (self.maximum - ray.origin.y) / ray.direction.y;
+        if Cone::check_cap(ray, t, self.maximum) {
+            xs.values.push(Intersection::new(t, self));
+        }
+    }
+
+    fn push_if_in_bounds<'a>(&'a self, ray: &Ray, t: f64, xs: &mut Intersections<'a>) {
+        let y = ray.origin.y + t * ray.direction.y;
+        if self.minimum < y && y < self.maximum {
+            xs.values.push(Intersection::new(t, self));
+        }
+    }
+}
+
+impl Shape for Cone {
+    shape_properties!();
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        let mut xs = Intersections::new();
+        let origin = local_ray.origin;
+        let direction = local_ray.direction;
+
+        let a = direction.x.powi(2) - direction.y.powi(2) + direction.z.powi(2);
+        let b = 2.0 * origin.x * direction.x - 2.0 * origin.y * direction.y + 2.0 * origin.z * direction.z;
+        let c = origin.x.powi(2) - origin.y.powi(2) + origin.z.powi(2);
+
+        if a.abs() < EPSILON {
+            // the ray is parallel to one of the cone's halves, so it hits the other half at most once
+            if b.abs() >= EPSILON {
+                self.push_if_in_bounds(local_ray, -c / (2.0 * b), &mut xs);
+            }
+        } else {
+            let discriminate = (b * b) - (4.0 * a * c);
+
+            if discriminate < 0.0 {
+                return xs;
+            }
+
+            let mut t0 = (-b - discriminate.sqrt()) / (2.0 * a);
+            let mut t1 = (-b + discriminate.sqrt()) / (2.0 * a);
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            self.push_if_in_bounds(local_ray, t0, &mut xs);
+            self.push_if_in_bounds(local_ray, t1, &mut xs);
+        }
+
+        self.intersect_caps(local_ray, &mut xs);
+
+        return xs;
+    }
+
+    fn local_normal_at(&self, local_point: Tuple) -> Tuple {
+        let dist = local_point.x.powi(2) + local_point.z.powi(2);
+
+        if dist < self.maximum.powi(2) && local_point.y >= self.maximum - EPSILON {
+            return Tuple::vector(0.0, 1.0, 0.0);
+        } else if dist < self.minimum.powi(2) && local_point.y <= self.minimum + EPSILON {
+            return Tuple::vector(0.0, -1.0, 0.0);
+        }
+
+        let mut y = dist.sqrt();
+        if local_point.y > 0.0 {
+            y = -y;
+        }
+
+        return Tuple::vector(local_point.x, y, local_point.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cone::Cone;
+    use crate::shape::Shape;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+
+    macro_rules! intersecting_a_cone_with_a_ray_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction, t0, t1) = $value;
+
+                let shape = Cone::new();
+                let r = Ray { origin, direction: direction.normalize() };
+
+                let xs = shape.local_intersect(&r);
+
+                assert_eq!(2, xs.len());
+                assert_eq!(t0, (xs[0].t * 100000.0).round() / 100000.0);
+                assert_eq!(t1, (xs[1].t * 100000.0).round() / 100000.0);
+            }
+        )*
+        }
+    }
+
+    intersecting_a_cone_with_a_ray_tests! {
+        intersecting_a_cone_straight_on: (Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 5.0, 5.0),
+        intersecting_a_cone_diagonally: (Tuple::point(0.0, 0.0, -5.0), Tuple::vector(1.0, 1.0, 1.0), 8.66025, 8.66025),
+        intersecting_a_cone_at_an_angle: (Tuple::point(1.0, 1.0, -5.0), Tuple::vector(-0.5, -1.0, 1.0), 4.55006, 49.44994),
+    }
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray_parallel_to_one_of_its_halves() {
+        let shape = Cone::new();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -1.0),
+            direction: Tuple::vector(0.0, 1.0, 1.0).normalize(),
+        };
+
+        let xs = shape.local_intersect(&r);
+
+        assert_eq!(1, xs.len());
+        assert_eq!(0.35355, (xs[0].t * 100000.0).round() / 100000.0);
+    }
+
+    #[test]
+    fn a_ray_through_the_tip_of_a_cone_parallel_to_its_surface_misses() {
+        let shape = Cone::new();
+        let r = Ray {
+            origin: Tuple::point(0.0, -1.0, -1.0),
+            direction: Tuple::vector(0.0, 1.0, 1.0).normalize(),
+        };
+
+        let xs = shape.local_intersect(&r);
+
+        assert_eq!(0, xs.len());
+    }
+
+    macro_rules! intersecting_a_cones_end_caps_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction, count) = $value;
+
+                let shape = Cone { minimum: -0.5, maximum: 0.5, closed: true, ..Cone::new() };
+                let r = Ray { origin, direction: direction.normalize() };
+
+                let xs = shape.local_intersect(&r);
+
+                assert_eq!(count, xs.len());
+            }
+        )*
+        }
+    }
+
+    intersecting_a_cones_end_caps_tests! {
+        intersecting_a_cones_end_caps_missing_both: (Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 1.0, 0.0), 0),
+        intersecting_a_cones_end_caps_through_a_cap_and_side: (Tuple::point(0.0, 0.0, -0.25), Tuple::vector(0.0, 1.0, 1.0), 2),
+        intersecting_a_cones_end_caps_through_both_caps: (Tuple::point(0.0, 0.0, -0.25), Tuple::vector(0.0, 1.0, 0.0), 4),
+    }
+
+    macro_rules! computing_the_normal_vector_on_a_cone_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, expected) = $value;
+
+                let shape = Cone::new();
+
+                let n = shape.local_normal_at(point);
+
+                assert_eq!(expected, n);
+            }
+        )*
+        }
+    }
+
+    computing_the_normal_vector_on_a_cone_tests! {
+        computing_the_normal_vector_on_a_cone_at_the_tip: (Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 0.0)),
+        computing_the_normal_vector_on_a_cone_upper_half: (Tuple::point(1.0, 1.0, 1.0), Tuple::vector(1.0, -2.0_f64.sqrt(), 1.0)),
+        computing_the_normal_vector_on_a_cone_lower_half: (Tuple::point(-1.0, -1.0, 0.0), Tuple::vector(-1.0, 1.0, 0.0)),
+    }
+
+    #[test]
+    fn the_normal_on_a_cones_end_caps() {
+        let shape = Cone { minimum: -1.0, maximum: 2.0, closed: true, ..Cone::new() };
+
+        assert_eq!(Tuple::vector(0.0, 1.0, 0.0), shape.local_normal_at(Tuple::point(0.5, 2.0, 0.5)));
+        assert_eq!(Tuple::vector(0.0, -1.0, 0.0), shape.local_normal_at(Tuple::point(0.2, -1.0, 0.1)));
+    }
+}
@@ -0,0 +1,367 @@
+use crate::matrix::Matrix4;
+use crate::tuple::Tuple;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    // lhit: the left operand was hit; inl/inr: the hit is inside the left/right operand
+    pub fn intersection_allowed(&self, lhit: bool, inl: bool, inr: bool) -> bool {
+        return match self {
+            CsgOperation::Union => (lhit && !inr) || (!lhit && !inl),
+            CsgOperation::Intersection => (lhit && inr) || (!lhit && inl),
+            CsgOperation::Difference => (lhit && !inr) || (!lhit && inl),
+        };
+    }
+}
+
+// constructive solid geometry: two shapes combined by a set operation
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct Csg {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+    pub operation: CsgOperation,
+    // the operands are only set in new, which hands them the csg's transform
+    left: Box<dyn Shape>,
+    right: Box<dyn Shape>,
+}
+
+impl Csg {
+    #[allow(dead_code)]
+    pub fn new(operation: CsgOperation, mut left: Box<dyn Shape>, mut right: Box<dyn Shape>) -> Self {
+        left.set_parent_transform(Matrix4::identity());
+        right.set_parent_transform(Matrix4::identity());
+
+        return Csg {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+            operation,
+            left,
+            right,
+        };
+    }
+
+    #[allow(dead_code)]
+    pub fn left(&self) -> &dyn Shape {
+        return self.left.as_ref();
+    }
+
+    #[allow(dead_code)]
+    pub fn right(&self) -> &dyn Shape {
+        return self.right.as_ref();
+    }
+
+    // keeps only the intersections on the surface of the combined shape
+    pub fn filter_intersections<'a>(&self, xs: Intersections<'a>) -> Intersections<'a> {
+        let mut inl = false;
+        let mut inr = false;
+        let mut values = Vec::new();
+
+        for i in xs.values {
+            let lhit = self.left.includes(i.object);
+
+            if self.operation.intersection_allowed(lhit, inl, inr) {
+                values.push(i);
+            }
+
+            if lhit {
+                inl = !inl;
+            } else {
+                inr = !inr;
+            }
+        }
+
+        return Intersections { values };
+    }
+
+    fn update_children(&mut self) {
+        let world_transform = self.parent_transform * self.transform;
+        self.left.set_parent_transform(world_transform);
+        self.right.set_parent_transform(world_transform);
+    }
+}
+
+impl Shape for Csg {
+    fn id(&self) -> usize {
+        return self.id;
+    }
+
+    fn transform(&self) -> Matrix4 {
+        return self.transform;
+    }
+
+    fn set_transform(&mut self, new_transform: Matrix4) {
+        self.transform = new_transform;
+        self.update_children();
+    }
+
+    fn material(&self) -> &Material {
+        return &self.material;
+    }
+
+    // the operands keep their own materials
+    fn set_material(&mut self, new_material: Material) {
+        self.material = new_material;
+    }
+
+    fn parent_transform(&self) -> Matrix4 {
+        return self.parent_transform;
+    }
+
+    fn set_parent_transform(&mut self, new_parent_transform: Matrix4) {
+        self.parent_transform = new_parent_transform;
+        self.update_children();
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        return self.id == other.id() || self.left.includes(other) || self.right.includes(other);
+    }
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        let mut values: Vec<Intersection> = local_ray.intersect(self.left.as_ref()).values;
+        values.extend(local_ray.intersect(self.right.as_ref()).values);
+
+        values.sort_by(|x, y| x.t.partial_cmp(&y.t).unwrap());
+
+        return self.filter_intersections(Intersections { values });
+    }
+
+    fn local_normal_at(&self, _local_point: Tuple) -> Tuple {
+        panic!("A CSG shape has no surface of its own, normals are computed on its operands");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csg::{Csg, CsgOperation};
+    use crate::shape::Shape;
+    use crate::sphere::Sphere;
+    use crate::cube::Cube;
+    use crate::group::Group;
+    use crate::matrix::Matrix4;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+    use crate::intersection::{Intersection, Intersections};
+    use crate::material::Material;
+    use crate::color::Color;
+
+    #[test]
+    fn csg_is_created_with_an_operation_and_two_shapes() {
+        let s1 = Sphere::new();
+        let s2 = Cube::new();
+
+        let c = Csg::new(CsgOperation::Union, Box::new(s1.clone()), Box::new(s2.clone()));
+
+        assert_eq!(CsgOperation::Union, c.operation);
+        assert_eq!(s1.id, c.left().id());
+        assert_eq!(s2.id, c.right().id());
+        assert!(c.includes(&s1));
+        assert!(c.includes(&s2));
+    }
+
+    #[test]
+    fn setting_the_material_of_a_csg_leaves_its_operands_alone() {
+        let mut c = Csg::new(CsgOperation::Union, Box::new(Sphere::new()), Box::new(Cube::new()));
+        let m = Material { color: Color::new(1.0, 0.0, 0.0), ..Material::default() };
+
+        c.set_material(m.clone());
+
+        assert_eq!(&m, c.material());
+        assert_eq!(&Material::default(), c.left().material());
+        assert_eq!(&Material::default(), c.right().material());
+    }
+
+    macro_rules! evaluating_the_rule_for_a_csg_operation_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (op, lhit, inl, inr, expected) = $value;
+
+                let result = CsgOperation::intersection_allowed(&op, lhit, inl, inr);
+
+                assert_eq!(expected, result);
+            }
+        )*
+        }
+    }
+
+    evaluating_the_rule_for_a_csg_operation_tests! {
+        union_rule_1: (CsgOperation::Union, true, true, true, false),
+        union_rule_2: (CsgOperation::Union, true, true, false, true),
+        union_rule_3: (CsgOperation::Union, true, false, true, false),
+        union_rule_4: (CsgOperation::Union, true, false, false, true),
+        union_rule_5: (CsgOperation::Union, false, true, true, false),
+        union_rule_6: (CsgOperation::Union, false, true, false, false),
+        union_rule_7: (CsgOperation::Union, false, false, true, true),
+        union_rule_8: (CsgOperation::Union, false, false, false, true),
+        intersection_rule_1: (CsgOperation::Intersection, true, true, true, true),
+        intersection_rule_2: (CsgOperation::Intersection, true, true, false, false),
+        intersection_rule_3: (CsgOperation::Intersection, true, false, true, true),
+        intersection_rule_4: (CsgOperation::Intersection, true, false, false, false),
+        intersection_rule_5: (CsgOperation::Intersection, false, true, true, true),
+        intersection_rule_6: (CsgOperation::Intersection, false, true, false, true),
+        intersection_rule_7: (CsgOperation::Intersection, false, false, true, false),
+        intersection_rule_8: (CsgOperation::Intersection, false, false, false, false),
+        difference_rule_1: (CsgOperation::Difference, true, true, true, false),
+        difference_rule_2: (CsgOperation::Difference, true, true, false, true),
+        difference_rule_3: (CsgOperation::Difference, true, false, true, false),
+        difference_rule_4: (CsgOperation::Difference, true, false, false, true),
+        difference_rule_5: (CsgOperation::Difference, false, true, true, true),
+        difference_rule_6: (CsgOperation::Difference, false, true, false, true),
+        difference_rule_7: (CsgOperation::Difference, false, false, true, false),
+        difference_rule_8: (CsgOperation::Difference, false, false, false, false),
+    }
+
+    macro_rules! filtering_a_list_of_intersections_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (op, x0, x1) = $value;
+
+                let s1 = Sphere::new();
+                let s2 = Cube::new();
+                let c = Csg::new(op, Box::new(s1.clone()), Box::new(s2.clone()));
+                let xs = Intersections {
+                    values: vec!(
+                        Intersection::new(1.0, &s1),
+                        Intersection::new(2.0, &s2),
+                        Intersection::new(3.0, &s1),
+                        Intersection::new(4.0, &s2),
+                    )
+                };
+
+                let result = c.filter_intersections(xs);
+
+                let actual: Vec<f64> = result.values.iter().map(|i| i.t).collect();
+                assert_eq!(vec!(x0, x1), actual);
+            }
+        )*
+        }
+    }
+
+    filtering_a_list_of_intersections_tests! {
+        filtering_a_list_of_intersections_for_union: (CsgOperation::Union, 1.0, 4.0),
+        filtering_a_list_of_intersections_for_intersection: (CsgOperation::Intersection, 2.0, 3.0),
+        filtering_a_list_of_intersections_for_difference: (CsgOperation::Difference, 1.0, 2.0),
+    }
+
+    #[test]
+    fn a_ray_misses_a_csg_object() {
+        let c = Csg::new(CsgOperation::Union, Box::new(Sphere::new()), Box::new(Cube::new()));
+        let r = Ray {
+            origin: Tuple::point(0.0, 2.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = c.local_intersect(&r);
+
+        assert_eq!(0, xs.len());
+    }
+
+    #[test]
+    fn a_ray_hits_a_csg_object() {
+        let s1 = Sphere::new();
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::translation(0.0, 0.0, 0.5));
+        let c = Csg::new(CsgOperation::Union, Box::new(s1.clone()), Box::new(s2.clone()));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = c.local_intersect(&r);
+
+        assert_eq!(2, xs.len());
+        assert_eq!(4.0, xs[0].t);
+        assert_eq!(s1.id, xs[0].object.id());
+        assert_eq!(6.5, xs[1].t);
+        assert_eq!(s2.id, xs[1].object.id());
+    }
+
+    #[test]
+    fn a_ray_hits_the_carved_surface_of_a_difference() {
+        let s1 = Sphere::new();
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::translation(0.0, 0.0, -0.5));
+        let c = Csg::new(CsgOperation::Difference, Box::new(s1.clone()), Box::new(s2.clone()));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = c.local_intersect(&r);
+
+        assert_eq!(2, xs.len());
+        assert_eq!(5.5, xs[0].t);
+        assert_eq!(s2.id, xs[0].object.id());
+        assert_eq!(6.0, xs[1].t);
+        assert_eq!(s1.id, xs[1].object.id());
+    }
+
+    #[test]
+    fn a_csg_operand_can_be_a_group() {
+        let s1 = Sphere::new();
+        let s2 = Sphere::new();
+        let mut g = Group::new();
+        g.add_child(Box::new(s2.clone()));
+        g.set_transform(Matrix4::translation(0.0, 0.0, 0.5));
+        let c = Csg::new(CsgOperation::Intersection, Box::new(s1.clone()), Box::new(g));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = c.local_intersect(&r);
+
+        assert_eq!(2, xs.len());
+        assert_eq!(4.5, xs[0].t);
+        assert_eq!(s2.id, xs[0].object.id());
+        assert_eq!(6.0, xs[1].t);
+        assert_eq!(s1.id, xs[1].object.id());
+    }
+
+    #[test]
+    fn a_csg_nested_inside_a_group_uses_the_full_transform_chain() {
+        let s1 = Sphere::new();
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::translation(0.0, 0.0, 0.5));
+        let mut c = Csg::new(CsgOperation::Union, Box::new(s1.clone()), Box::new(s2.clone()));
+        c.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let mut g = Group::new();
+        g.add_child(Box::new(c));
+        g.set_transform(Matrix4::translation(0.0, 0.0, 10.0));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = r.intersect(&g);
+
+        assert_eq!(2, xs.len());
+        assert_eq!(8.0, xs[0].t);
+        assert_eq!(s1.id, xs[0].object.id());
+        assert_eq!(13.0, xs[1].t);
+        assert_eq!(s2.id, xs[1].object.id());
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), xs[0].object.normal_at(Tuple::point(0.0, 0.0, 8.0)).round());
+        assert_eq!(Tuple::vector(0.0, 0.0, 1.0), xs[1].object.normal_at(Tuple::point(0.0, 0.0, 13.0)).round());
+        assert!(g.includes(&s2));
+    }
+}
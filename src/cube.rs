@@ -0,0 +1,195 @@
+use crate::matrix::Matrix4;
+use crate::tuple::{Tuple, EPSILON};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+use crate::shape_properties;
+
+// an axis-aligned cube spanning -1..1 on every axis in object space
+#[allow(dead_code)]
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Cube {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+}
+
+impl Cube {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        return Cube {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+        };
+    }
+
+    // the t values where the ray enters and leaves the slab between -1 and 1 on one axis
+    fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+        let tmin_numerator = -1.0 - origin;
+        let tmax_numerator = 1.0 - origin;
+
+        let (tmin, tmax) = if direction.abs() >= EPSILON {
+            (tmin_numerator / direction, tmax_numerator / direction)
+        } else {
+            (tmin_numerator * f64::INFINITY, tmax_numerator * f64::INFINITY)
+        };
+
+        if tmin > tmax {
+            return (tmax, tmin);
+        }
+
+        return (tmin, tmax);
+    }
+}
+
+impl Shape for Cube {
+    shape_properties!();
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        let (xtmin, xtmax) = Cube::check_axis(local_ray.origin.x, local_ray.direction.x);
+        let (ytmin, ytmax) = Cube::check_axis(local_ray.origin.y, local_ray.direction.y);
+        let (ztmin, ztmax) = Cube::check_axis(local_ray.origin.z, local_ray.direction.z);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return Intersections::new();
+        }
+
+        return Intersections {
+            values: vec!(
+                Intersection::new(tmin, self),
+                Intersection::new(tmax, self)
+            )
+        };
+    }
+
+    fn local_normal_at(&self, local_point: Tuple) -> Tuple {
+        let maxc = local_point.x.abs().max(local_point.y.abs()).max(local_point.z.abs());
+
+        if maxc == local_point.x.abs() {
+            return Tuple::vector(local_point.x, 0.0, 0.0);
+        } else if maxc == local_point.y.abs() {
+            return Tuple::vector(0.0, local_point.y, 0.0);
+        }
+
+        return Tuple::vector(0.0, 0.0, local_point.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::Cube;
+    use crate::shape::Shape;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+    use crate::matrix::Matrix4;
+
+    macro_rules! a_ray_intersects_a_cube_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction, t1, t2) = $value;
+
+                let c = Cube::new();
+                let r = Ray { origin, direction };
+
+                let xs = c.local_intersect(&r);
+
+                assert_eq!(2, xs.len());
+                assert_eq!(t1, xs[0].t);
+                assert_eq!(t2, xs[1].t);
+            }
+        )*
+        }
+    }
+
+    a_ray_intersects_a_cube_tests! {
+        a_ray_intersects_a_cube_from_positive_x: (Tuple::point(5.0, 0.5, 0.0), Tuple::vector(-1.0, 0.0, 0.0), 4.0, 6.0),
+        a_ray_intersects_a_cube_from_negative_x: (Tuple::point(-5.0, 0.5, 0.0), Tuple::vector(1.0, 0.0, 0.0), 4.0, 6.0),
+        a_ray_intersects_a_cube_from_positive_y: (Tuple::point(0.5, 5.0, 0.0), Tuple::vector(0.0, -1.0, 0.0), 4.0, 6.0),
+        a_ray_intersects_a_cube_from_negative_y: (Tuple::point(0.5, -5.0, 0.0), Tuple::vector(0.0, 1.0, 0.0), 4.0, 6.0),
+        a_ray_intersects_a_cube_from_positive_z: (Tuple::point(0.5, 0.0, 5.0), Tuple::vector(0.0, 0.0, -1.0), 4.0, 6.0),
+        a_ray_intersects_a_cube_from_negative_z: (Tuple::point(0.5, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 4.0, 6.0),
+        a_ray_intersects_a_cube_from_the_inside: (Tuple::point(0.0, 0.5, 0.0), Tuple::vector(0.0, 0.0, 1.0), -1.0, 1.0),
+    }
+
+    macro_rules! a_ray_misses_a_cube_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction) = $value;
+
+                let c = Cube::new();
+                let r = Ray { origin, direction: direction.normalize() };
+
+                let xs = c.local_intersect(&r);
+
+                assert_eq!(0, xs.len());
+            }
+        )*
+        }
+    }
+
+    a_ray_misses_a_cube_tests! {
+        a_ray_misses_a_cube_diagonal_from_negative_x: (Tuple::point(-2.0, 0.0, 0.0), Tuple::vector(0.2673, 0.5345, 0.8018)),
+        a_ray_misses_a_cube_diagonal_from_negative_y: (Tuple::point(0.0, -2.0, 0.0), Tuple::vector(0.8018, 0.2673, 0.5345)),
+        a_ray_misses_a_cube_diagonal_from_negative_z: (Tuple::point(0.0, 0.0, -2.0), Tuple::vector(0.5345, 0.8018, 0.2673)),
+        a_ray_misses_a_cube_parallel_to_z: (Tuple::point(2.0, 0.0, 2.0), Tuple::vector(0.0, 0.0, -1.0)),
+        a_ray_misses_a_cube_parallel_to_x: (Tuple::point(0.0, 2.0, 2.0), Tuple::vector(0.0, -1.0, 0.0)),
+        a_ray_misses_a_cube_parallel_to_y: (Tuple::point(2.0, 2.0, 0.0), Tuple::vector(-1.0, 0.0, 0.0)),
+    }
+
+    macro_rules! the_normal_on_the_surface_of_a_cube_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, expected) = $value;
+
+                let c = Cube::new();
+
+                let normal = c.local_normal_at(point);
+
+                assert_eq!(expected, normal);
+            }
+        )*
+        }
+    }
+
+    the_normal_on_the_surface_of_a_cube_tests! {
+        the_normal_on_the_positive_x_face: (Tuple::point(1.0, 0.5, -0.8), Tuple::vector(1.0, 0.0, 0.0)),
+        the_normal_on_the_negative_x_face: (Tuple::point(-1.0, -0.2, 0.9), Tuple::vector(-1.0, 0.0, 0.0)),
+        the_normal_on_the_positive_y_face: (Tuple::point(-0.4, 1.0, -0.1), Tuple::vector(0.0, 1.0, 0.0)),
+        the_normal_on_the_negative_y_face: (Tuple::point(0.3, -1.0, -0.7), Tuple::vector(0.0, -1.0, 0.0)),
+        the_normal_on_the_positive_z_face: (Tuple::point(-0.6, 0.3, 1.0), Tuple::vector(0.0, 0.0, 1.0)),
+        the_normal_on_the_negative_z_face: (Tuple::point(0.4, 0.4, -1.0), Tuple::vector(0.0, 0.0, -1.0)),
+        the_normal_on_a_corner_positive: (Tuple::point(1.0, 1.0, 1.0), Tuple::vector(1.0, 0.0, 0.0)),
+        the_normal_on_a_corner_negative: (Tuple::point(-1.0, -1.0, -1.0), Tuple::vector(-1.0, 0.0, 0.0)),
+    }
+
+    #[test]
+    fn intersecting_a_transformed_cube() {
+        let mut c = Cube::new();
+        c.set_transform(Matrix4::scaling(2.0, 1.0, 1.0).translate(3.0, 0.0, 0.0));
+        let r = Ray {
+            origin: Tuple::point(-5.0, 0.0, 0.0),
+            direction: Tuple::vector(1.0, 0.0, 0.0),
+        };
+
+        let xs = r.intersect(&c);
+
+        assert_eq!(2, xs.len());
+        assert_eq!(6.0, xs[0].t);
+        assert_eq!(10.0, xs[1].t);
+    }
+}
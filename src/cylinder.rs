@@ -0,0 +1,290 @@
+use crate::matrix::Matrix4;
+use crate::tuple::{Tuple, EPSILON};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+use crate::shape_properties;
+
+// a cylinder of radius 1 around the y axis, optionally truncated and capped
+#[allow(dead_code)]
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Cylinder {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cylinder {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        return Cylinder {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        };
+    }
+
+    // checks if the intersection at t is within the unit radius of the y axis
+    fn check_cap(ray: &Ray, t: f64) -> bool {
+        let x = ray.origin.x + t * ray.direction.x;
+        let z = ray.origin.z + t * ray.direction.z;
+
+        return (x * x + z * z) <= 1.0;
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Intersections<'a>) {
+        // caps only matter if the cylinder is closed and might be hit
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+
+        let t = (self.minimum - ray.origin.y) / ray.direction.y;
+        if Cylinder::check_cap(ray, t) {
+            xs.values.push(Intersection::new(t, self));
+        }
+
+        let t = (self.maximum - ray.origin.y) / ray.direction.y;
+        if Cylinder::check_cap(ray, t) {
+            xs.values.push(Intersection::new(t, self));
+        }
+    }
+}
+
+impl Shape for Cylinder {
+    shape_properties!();
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        let mut xs = Intersections::new();
+
+        let a = local_ray.direction.x.powi(2) + local_ray.direction.z.powi(2);
+
+        // a ray parallel to the y axis can only hit the caps
+        if a.abs() >= EPSILON {
+            let b = 2.0 * local_ray.origin.x * local_ray.direction.x +
+                2.0 * local_ray.origin.z * local_ray.direction.z;
+            let c = local_ray.origin.x.powi(2) + local_ray.origin.z.powi(2) - 1.0;
+
+            let discriminate = (b * b) - (4.0 * a * c);
+
+            if discriminate < 0.0 {
+                return xs;
+            }
+
+            let mut t0 = (-b - discriminate.sqrt()) / (2.0 * a);
+            let mut t1 = (-b + discriminate.sqrt()) / (2.0 * a);
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            for t in [t0, t1] {
+                let y = local_ray.origin.y + t * local_ray.direction.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.values.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(local_ray, &mut xs);
+
+        return xs;
+    }
+
+    fn local_normal_at(&self, local_point: Tuple) -> Tuple {
+        let dist = local_point.x.powi(2) + local_point.z.powi(2);
+
+        if dist < 1.0 && local_point.y >= self.maximum - EPSILON {
+            return Tuple::vector(0.0, 1.0, 0.0);
+        } else if dist < 1.0 && local_point.y <= self.minimum + EPSILON {
+            return Tuple::vector(0.0, -1.0, 0.0);
+        }
+
+        return Tuple::vector(local_point.x, 0.0, local_point.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cylinder::Cylinder;
+    use crate::shape::Shape;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+
+    macro_rules! a_ray_misses_a_cylinder_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction) = $value;
+
+                let cyl = Cylinder::new();
+                let r = Ray { origin, direction: direction.normalize() };
+
+                let xs = cyl.local_intersect(&r);
+
+                assert_eq!(0, xs.len());
+            }
+        )*
+        }
+    }
+
+    a_ray_misses_a_cylinder_tests! {
+        a_ray_misses_a_cylinder_on_the_surface: (Tuple::point(1.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        a_ray_misses_a_cylinder_inside_parallel_to_the_axis: (Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        a_ray_misses_a_cylinder_skewed: (Tuple::point(0.0, 0.0, -5.0), Tuple::vector(1.0, 1.0, 1.0)),
+    }
+
+    macro_rules! a_ray_strikes_a_cylinder_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction, t0, t1) = $value;
+
+                let cyl = Cylinder::new();
+                let r = Ray { origin, direction: direction.normalize() };
+
+                let xs = cyl.local_intersect(&r);
+
+                assert_eq!(2, xs.len());
+                assert_eq!(t0, (xs[0].t * 100000.0).round() / 100000.0);
+                assert_eq!(t1, (xs[1].t * 100000.0).round() / 100000.0);
+            }
+        )*
+        }
+    }
+
+    a_ray_strikes_a_cylinder_tests! {
+        a_ray_strikes_a_cylinder_on_a_tangent: (Tuple::point(1.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 5.0, 5.0),
+        a_ray_strikes_a_cylinder_through_the_middle: (Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 4.0, 6.0),
+        a_ray_strikes_a_cylinder_at_an_angle: (Tuple::point(0.5, 0.0, -5.0), Tuple::vector(0.1, 1.0, 1.0), 6.80798, 7.08872),
+    }
+
+    macro_rules! normal_vector_on_a_cylinder_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, expected) = $value;
+
+                let cyl = Cylinder::new();
+
+                let n = cyl.local_normal_at(point);
+
+                assert_eq!(expected, n);
+            }
+        )*
+        }
+    }
+
+    normal_vector_on_a_cylinder_tests! {
+        normal_vector_on_a_cylinder_positive_x: (Tuple::point(1.0, 0.0, 0.0), Tuple::vector(1.0, 0.0, 0.0)),
+        normal_vector_on_a_cylinder_negative_z: (Tuple::point(0.0, 5.0, -1.0), Tuple::vector(0.0, 0.0, -1.0)),
+        normal_vector_on_a_cylinder_positive_z: (Tuple::point(0.0, -2.0, 1.0), Tuple::vector(0.0, 0.0, 1.0)),
+        normal_vector_on_a_cylinder_negative_x: (Tuple::point(-1.0, 1.0, 0.0), Tuple::vector(-1.0, 0.0, 0.0)),
+    }
+
+    #[test]
+    fn the_default_minimum_and_maximum_for_a_cylinder() {
+        let cyl = Cylinder::new();
+
+        assert_eq!(f64::NEG_INFINITY, cyl.minimum);
+        assert_eq!(f64::INFINITY, cyl.maximum);
+    }
+
+    #[test]
+    fn the_default_closed_value_for_a_cylinder() {
+        let cyl = Cylinder::new();
+
+        assert!(!cyl.closed);
+    }
+
+    macro_rules! intersecting_a_constrained_cylinder_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, direction, count) = $value;
+
+                let cyl = Cylinder { minimum: 1.0, maximum: 2.0, ..Cylinder::new() };
+                let r = Ray { origin: point, direction: direction.normalize() };
+
+                let xs = cyl.local_intersect(&r);
+
+                assert_eq!(count, xs.len());
+            }
+        )*
+        }
+    }
+
+    intersecting_a_constrained_cylinder_tests! {
+        constrained_cylinder_ray_escapes_through_the_open_top: (Tuple::point(0.0, 1.5, 0.0), Tuple::vector(0.1, 1.0, 0.0), 0),
+        constrained_cylinder_ray_passes_above: (Tuple::point(0.0, 3.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 0),
+        constrained_cylinder_ray_passes_below: (Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 0),
+        constrained_cylinder_ray_at_the_maximum_is_excluded: (Tuple::point(0.0, 2.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 0),
+        constrained_cylinder_ray_at_the_minimum_is_excluded: (Tuple::point(0.0, 1.0, -5.0), Tuple::vector(0.0, 0.0, 1.0), 0),
+        constrained_cylinder_ray_through_the_middle: (Tuple::point(0.0, 1.5, -2.0), Tuple::vector(0.0, 0.0, 1.0), 2),
+    }
+
+    macro_rules! intersecting_the_caps_of_a_closed_cylinder_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, direction, count) = $value;
+
+                let cyl = Cylinder { minimum: 1.0, maximum: 2.0, closed: true, ..Cylinder::new() };
+                let r = Ray { origin: point, direction: direction.normalize() };
+
+                let xs = cyl.local_intersect(&r);
+
+                assert_eq!(count, xs.len());
+            }
+        )*
+        }
+    }
+
+    intersecting_the_caps_of_a_closed_cylinder_tests! {
+        closed_cylinder_ray_down_through_both_caps: (Tuple::point(0.0, 3.0, 0.0), Tuple::vector(0.0, -1.0, 0.0), 2),
+        closed_cylinder_ray_through_top_cap_and_side: (Tuple::point(0.0, 3.0, -2.0), Tuple::vector(0.0, -1.0, 2.0), 2),
+        closed_cylinder_ray_through_top_cap_at_the_corner: (Tuple::point(0.0, 4.0, -2.0), Tuple::vector(0.0, -1.0, 1.0), 2),
+        closed_cylinder_ray_through_bottom_cap_and_side: (Tuple::point(0.0, 0.0, -2.0), Tuple::vector(0.0, 1.0, 2.0), 2),
+        closed_cylinder_ray_through_bottom_cap_at_the_corner: (Tuple::point(0.0, -1.0, -2.0), Tuple::vector(0.0, 1.0, 1.0), 2),
+    }
+
+    macro_rules! the_normal_on_a_cylinder_end_cap_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, expected) = $value;
+
+                let cyl = Cylinder { minimum: 1.0, maximum: 2.0, closed: true, ..Cylinder::new() };
+
+                let n = cyl.local_normal_at(point);
+
+                assert_eq!(expected, n);
+            }
+        )*
+        }
+    }
+
+    the_normal_on_a_cylinder_end_cap_tests! {
+        the_normal_on_the_bottom_cap_center: (Tuple::point(0.0, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0)),
+        the_normal_on_the_bottom_cap_off_x: (Tuple::point(0.5, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0)),
+        the_normal_on_the_bottom_cap_off_z: (Tuple::point(0.0, 1.0, 0.5), Tuple::vector(0.0, -1.0, 0.0)),
+        the_normal_on_the_top_cap_center: (Tuple::point(0.0, 2.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        the_normal_on_the_top_cap_off_x: (Tuple::point(0.5, 2.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        the_normal_on_the_top_cap_off_z: (Tuple::point(0.0, 2.0, 0.5), Tuple::vector(0.0, 1.0, 0.0)),
+    }
+}
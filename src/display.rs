@@ -0,0 +1,242 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+// compresses unbounded linear radiance into the 0..1 range an 8-bit image can hold
+#[allow(dead_code)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum ToneMapping {
+    // values above 1 clip
+    Clamp,
+    // L / (1 + L) on luminance, nothing ever reaches white
+    Reinhard,
+    // like Reinhard, but luminance `white` and above maps to 1
+    ExtendedReinhard { white: f64 },
+    // Narkowicz's fit of the ACES filmic curve, applied per channel
+    AcesFilmic,
+}
+
+// Rec. 709 weights, matching the sRGB primaries
+fn luminance(color: Color) -> f64 {
+    return 0.2126 * color.red + 0.7152 * color.green + 0.0722 * color.blue;
+}
+
+// scales the color so its luminance becomes mapped(luminance), keeping its hue
+fn scale_luminance(color: Color, mapped: impl Fn(f64) -> f64) -> Color {
+    let l = luminance(color);
+    if l <= 0.0 {
+        return color;
+    }
+
+    return color * (mapped(l) / l);
+}
+
+fn aces_filmic(x: f64) -> f64 {
+    let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+
+    return (x * (a * x + b)) / (x * (c * x + d) + e);
+}
+
+impl ToneMapping {
+    pub fn apply(&self, color: Color) -> Color {
+        return match *self {
+            ToneMapping::Clamp => color,
+            ToneMapping::Reinhard => scale_luminance(color, |l| l / (1.0 + l)),
+            ToneMapping::ExtendedReinhard { white } =>
+                scale_luminance(color, |l| l * (1.0 + l / (white * white)) / (1.0 + l)),
+            ToneMapping::AcesFilmic => Color::new(
+                aces_filmic(color.red.max(0.0)),
+                aces_filmic(color.green.max(0.0)),
+                aces_filmic(color.blue.max(0.0)),
+            ),
+        };
+    }
+}
+
+// the sRGB transfer function, linear 0..1 to encoded 0..1
+pub fn srgb_encode(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        return 12.92 * value;
+    }
+
+    return 1.055 * value.powf(1.0 / 2.4) - 0.055;
+}
+
+// turns a linear render into display values: exposure, then tone mapping, clamping and sRGB encoding
+// and says how PPM and PNG files store them
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub struct DisplayTransform {
+    // in stops, every stop doubles the brightness
+    pub exposure: f64,
+    pub tone_mapping: ToneMapping,
+    pub srgb: bool,
+    // 16 instead of 8 bits per channel
+    pub sixteen_bit: bool,
+    // P6 instead of plain text P3
+    pub binary_ppm: bool,
+}
+
+impl Default for DisplayTransform {
+    fn default() -> Self {
+        return DisplayTransform {
+            exposure: 0.0,
+            tone_mapping: ToneMapping::Clamp,
+            srgb: true,
+            sixteen_bit: false,
+            binary_ppm: false,
+        };
+    }
+}
+
+impl DisplayTransform {
+    pub fn apply(&self, color: Color) -> Color {
+        let mapped = self.tone_mapping.apply(color * 2f64.powf(self.exposure));
+
+        let encode = |value: f64| {
+            let clamped = value.clamp(0.0, 1.0);
+            return if self.srgb { srgb_encode(clamped) } else { clamped };
+        };
+        return Color::new(encode(mapped.red), encode(mapped.green), encode(mapped.blue));
+    }
+}
+
+impl Canvas {
+    pub fn to_display(&self, transform: &DisplayTransform) -> Canvas {
+        let mut display = Canvas::new(self.width(), self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                display.write_pixel(x, y, transform.apply(self.pixel_at(x, y)));
+                display.write_alpha(x, y, self.alpha_at(x, y));
+            }
+        }
+
+        return display;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::display::{ToneMapping, DisplayTransform, srgb_encode, luminance};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+
+    macro_rules! srgb_encode_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (linear, expected) = $value;
+
+                assert_eq!(expected, (srgb_encode(linear) * 100000.0).round() / 100000.0);
+            }
+        )*
+        }
+    }
+
+    srgb_encode_tests! {
+        srgb_encode_black: (0.0, 0.0),
+        srgb_encode_white: (1.0, 1.0),
+        srgb_encode_linear_segment: (0.002, 0.02584),
+        srgb_encode_mid_gray: (0.18, 0.46136),
+        srgb_encode_half: (0.5, 0.73536),
+    }
+
+    macro_rules! tone_mapping_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (tone_mapping, color, expected) = $value;
+
+                assert_eq!(expected, tone_mapping.apply(color).round());
+            }
+        )*
+        }
+    }
+
+    tone_mapping_tests! {
+        clamp_leaves_colors_alone: (ToneMapping::Clamp, Color::new(2.0, 0.5, -1.0), Color::new(2.0, 0.5, -1.0)),
+        reinhard_of_gray: (ToneMapping::Reinhard, Color::new(1.0, 1.0, 1.0), Color::new(0.5, 0.5, 0.5)),
+        reinhard_of_bright_gray: (ToneMapping::Reinhard, Color::new(3.0, 3.0, 3.0), Color::new(0.75, 0.75, 0.75)),
+        reinhard_keeps_black: (ToneMapping::Reinhard, Color::new(0.0, 0.0, 0.0), Color::new(0.0, 0.0, 0.0)),
+        reinhard_keeps_the_hue: (ToneMapping::Reinhard, Color::new(2.0, 1.0, 0.0), Color::new(0.9344, 0.4672, 0.0)),
+        extended_reinhard_maps_white_to_one: (ToneMapping::ExtendedReinhard { white: 4.0 }, Color::new(4.0, 4.0, 4.0), Color::new(1.0, 1.0, 1.0)),
+        extended_reinhard_of_gray: (ToneMapping::ExtendedReinhard { white: 4.0 }, Color::new(1.0, 1.0, 1.0), Color::new(0.53125, 0.53125, 0.53125)),
+        aces_of_black: (ToneMapping::AcesFilmic, Color::new(0.0, 0.0, 0.0), Color::new(0.0, 0.0, 0.0)),
+        aces_of_one: (ToneMapping::AcesFilmic, Color::new(1.0, 0.18, -1.0), Color::new(0.8038, 0.2669, 0.0)),
+        aces_of_a_bright_value: (ToneMapping::AcesFilmic, Color::new(100.0, 100.0, 100.0), Color::new(1.03054, 1.03054, 1.03054)),
+    }
+
+    #[test]
+    fn reinhard_preserves_the_luminance_mapping() {
+        let color = Color::new(2.0, 1.0, 0.5);
+        let l = luminance(color);
+
+        let mapped = ToneMapping::Reinhard.apply(color);
+
+        assert_eq!(((l / (1.0 + l)) * 100000.0).round() / 100000.0, (luminance(mapped) * 100000.0).round() / 100000.0);
+    }
+
+    #[test]
+    fn the_default_display_transform_clamps_and_encodes_srgb() {
+        let transform = DisplayTransform::default();
+
+        let color = transform.apply(Color::new(1.5, 0.5, -0.5));
+
+        assert_eq!(Color::new(1.0, 0.73536, 0.0), color.round());
+    }
+
+    #[test]
+    fn exposure_is_applied_in_stops_before_tone_mapping() {
+        let transform = DisplayTransform { exposure: 1.0, tone_mapping: ToneMapping::Reinhard, srgb: false, ..DisplayTransform::default() };
+
+        let color = transform.apply(Color::new(0.5, 0.5, 0.5));
+
+        assert_eq!(Color::new(0.5, 0.5, 0.5), color.round());
+    }
+
+    #[test]
+    fn negative_exposure_darkens() {
+        let transform = DisplayTransform { exposure: -2.0, tone_mapping: ToneMapping::Clamp, srgb: false, ..DisplayTransform::default() };
+
+        let color = transform.apply(Color::new(2.0, 1.0, 0.0));
+
+        assert_eq!(Color::new(0.5, 0.25, 0.0), color.round());
+    }
+
+    #[test]
+    fn tone_mapped_values_are_clamped_before_encoding() {
+        let transform = DisplayTransform { exposure: 0.0, tone_mapping: ToneMapping::AcesFilmic, ..DisplayTransform::default() };
+
+        let color = transform.apply(Color::new(100.0, 0.0, f64::NAN));
+
+        assert_eq!(1.0, color.round().red);
+        assert_eq!(0.0, color.green);
+    }
+
+    #[test]
+    fn a_canvas_is_converted_pixel_by_pixel() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(0.18, 0.18, 0.18));
+        c.write_pixel(1, 0, Color::new(3.0, 3.0, 3.0));
+
+        let display = c.to_display(&DisplayTransform::default());
+
+        assert_eq!(Color::new(0.46136, 0.46136, 0.46136), display.pixel_at(0, 0).round());
+        assert_eq!(Color::new(1.0, 1.0, 1.0), display.pixel_at(1, 0).round());
+        assert_eq!(Color::new(3.0, 3.0, 3.0), c.pixel_at(1, 0));
+    }
+
+    #[test]
+    fn the_display_transform_keeps_the_alpha() {
+        let mut c = Canvas::new(1, 1);
+        c.write_alpha(0, 0, 0.5);
+
+        let display = c.to_display(&DisplayTransform::default());
+
+        assert_eq!(0.5, display.alpha_at(0, 0));
+    }
+}
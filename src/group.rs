@@ -0,0 +1,290 @@
+use crate::matrix::Matrix4;
+use crate::tuple::Tuple;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+
+// a collection of shapes that are transformed together
+#[derive(Debug)]
+pub struct Group {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+    // only add_child inserts children, it hands them the group's transform
+    children: Vec<Box<dyn Shape>>,
+}
+
+impl Group {
+    pub fn new() -> Self {
+        return Group {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+            children: Vec::new(),
+        };
+    }
+
+    pub fn add_child(&mut self, mut child: Box<dyn Shape>) {
+        child.set_parent_transform(self.parent_transform * self.transform);
+        self.children.push(child);
+    }
+
+    #[allow(dead_code)]
+    pub fn children(&self) -> &[Box<dyn Shape>] {
+        return &self.children;
+    }
+
+    // keeps every child's view of its parents in sync after this group moved
+    fn update_children(&mut self) {
+        let world_transform = self.parent_transform * self.transform;
+        for child in self.children.iter_mut() {
+            child.set_parent_transform(world_transform);
+        }
+    }
+}
+
+impl Shape for Group {
+    fn id(&self) -> usize {
+        return self.id;
+    }
+
+    fn transform(&self) -> Matrix4 {
+        return self.transform;
+    }
+
+    fn set_transform(&mut self, new_transform: Matrix4) {
+        self.transform = new_transform;
+        self.update_children();
+    }
+
+    fn material(&self) -> &Material {
+        return &self.material;
+    }
+
+    // the children keep their own materials
+    fn set_material(&mut self, new_material: Material) {
+        self.material = new_material;
+    }
+
+    fn parent_transform(&self) -> Matrix4 {
+        return self.parent_transform;
+    }
+
+    fn set_parent_transform(&mut self, new_parent_transform: Matrix4) {
+        self.parent_transform = new_parent_transform;
+        self.update_children();
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        return self.id == other.id() || self.children.iter().any(|child| child.includes(other));
+    }
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        let mut values: Vec<Intersection> = self.children.iter()
+            .flat_map(|child| local_ray.intersect(child.as_ref()).values)
+            .collect();
+
+        values.sort_by(|x, y| x.t.partial_cmp(&y.t).unwrap());
+
+        return Intersections { values };
+    }
+
+    fn local_normal_at(&self, _local_point: Tuple) -> Tuple {
+        panic!("A group has no surface of its own, normals are computed on its children");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use crate::group::Group;
+    use crate::shape::Shape;
+    use crate::shape::tests::TestShape;
+    use crate::sphere::Sphere;
+    use crate::matrix::Matrix4;
+    use crate::material::Material;
+    use crate::color::Color;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+
+    #[test]
+    fn creating_a_new_group() {
+        let g = Group::new();
+
+        assert_eq!(Matrix4::identity(), g.transform());
+        assert_eq!(0, g.children.len());
+    }
+
+    #[test]
+    fn adding_a_child_to_a_group() {
+        let mut g = Group::new();
+        g.set_transform(Matrix4::translation(1.0, 2.0, 3.0));
+        let s = TestShape::new();
+        let s_id = s.id();
+
+        g.add_child(Box::new(s));
+
+        assert_eq!(1, g.children.len());
+        assert_eq!(s_id, g.children[0].id());
+        assert_eq!(Matrix4::translation(1.0, 2.0, 3.0), g.children[0].parent_transform());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_an_empty_group() {
+        let g = Group::new();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = g.local_intersect(&r);
+
+        assert_eq!(0, xs.len());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_nonempty_group() {
+        let mut g = Group::new();
+        let s1 = Sphere::new();
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::translation(0.0, 0.0, -3.0));
+        let mut s3 = Sphere::new();
+        s3.set_transform(Matrix4::translation(5.0, 0.0, 0.0));
+        let (s1_id, s2_id) = (s1.id, s2.id);
+        g.add_child(Box::new(s1));
+        g.add_child(Box::new(s2));
+        g.add_child(Box::new(s3));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = g.local_intersect(&r);
+
+        assert_eq!(4, xs.len());
+        assert_eq!(s2_id, xs[0].object.id());
+        assert_eq!(s2_id, xs[1].object.id());
+        assert_eq!(s1_id, xs[2].object.id());
+        assert_eq!(s1_id, xs[3].object.id());
+    }
+
+    #[test]
+    fn intersecting_a_transformed_group() {
+        let mut g = Group::new();
+        g.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(5.0, 0.0, 0.0));
+        g.add_child(Box::new(s));
+        let r = Ray {
+            origin: Tuple::point(10.0, 0.0, -10.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = r.intersect(&g);
+
+        assert_eq!(2, xs.len());
+    }
+
+    // g1 (rotated) contains g2 (scaled) which contains a translated sphere
+    fn nested_groups(g2_scaling: Matrix4) -> Group {
+        let mut g1 = Group::new();
+        g1.set_transform(Matrix4::rotation_y(PI / 2.0));
+        let mut g2 = Group::new();
+        g2.set_transform(g2_scaling);
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(5.0, 0.0, 0.0));
+        g2.add_child(Box::new(s));
+        g1.add_child(Box::new(g2));
+
+        return g1;
+    }
+
+    #[test]
+    fn converting_a_point_from_world_to_object_space() {
+        let g1 = nested_groups(Matrix4::scaling(2.0, 2.0, 2.0));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, -1.0),
+        };
+        let xs = r.intersect(&g1);
+        let s = xs[0].object;
+
+        let p = s.world_to_object(Tuple::point(-2.0, 0.0, -10.0));
+
+        assert_eq!(Tuple::point(0.0, 0.0, -1.0), p.round());
+    }
+
+    #[test]
+    fn converting_a_normal_from_object_to_world_space() {
+        let g1 = nested_groups(Matrix4::scaling(1.0, 2.0, 3.0));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, -1.0),
+        };
+        let xs = r.intersect(&g1);
+        let s = xs[0].object;
+
+        let n = s.normal_to_world(Tuple::vector(3.0_f64.sqrt() / 3.0, 3.0_f64.sqrt() / 3.0, 3.0_f64.sqrt() / 3.0));
+
+        assert_eq!(Tuple::vector(0.28571, 0.42857, -0.85714), n.round());
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_child_object() {
+        let g1 = nested_groups(Matrix4::scaling(1.0, 2.0, 3.0));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, -1.0),
+        };
+        let xs = r.intersect(&g1);
+        let s = xs[0].object;
+
+        let n = s.normal_at(Tuple::point(1.7321, 1.1547, -5.5774));
+
+        assert_eq!(Tuple::vector(0.2857, 0.42854, -0.85716), n.round());
+    }
+
+    #[test]
+    fn transforming_a_group_after_adding_children_moves_them() {
+        let mut g = Group::new();
+        g.add_child(Box::new(Sphere::new()));
+        g.set_transform(Matrix4::translation(0.0, 0.0, 10.0));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = r.intersect(&g);
+
+        assert_eq!(9.0, xs[0].t);
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), xs[0].object.normal_at(Tuple::point(0.0, 0.0, 9.0)));
+    }
+
+    #[test]
+    fn setting_the_material_of_a_group_leaves_its_children_alone() {
+        let mut g = Group::new();
+        g.add_child(Box::new(Sphere::new()));
+        let m = Material { color: Color::new(1.0, 0.0, 0.0), ..Material::default() };
+
+        g.set_material(m.clone());
+
+        assert_eq!(&m, g.material());
+        assert_eq!(&Material::default(), g.children[0].material());
+    }
+
+    #[test]
+    fn a_group_includes_its_nested_children() {
+        let mut g1 = Group::new();
+        let mut g2 = Group::new();
+        let s = Sphere::new();
+        let other = Sphere::new();
+        g2.add_child(Box::new(s.clone()));
+        g1.add_child(Box::new(g2));
+
+        assert!(g1.includes(&s));
+        assert!(!g1.includes(&other));
+    }
+}
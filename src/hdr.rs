@@ -0,0 +1,475 @@
+use std::io::{self, Write};
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+// run-length encoded scanlines must be between these widths
+const MIN_RLE_WIDTH: usize = 8;
+const MAX_RLE_WIDTH: usize = 0x7fff;
+
+// shorter runs are cheaper to store as literal bytes
+const MIN_RUN_LENGTH: usize = 4;
+
+// shared 8-bit mantissas for red, green and blue plus a common exponent biased by 128
+pub fn color_to_rgbe(color: Color) -> [u8; 4] {
+    // RGBE cannot store negative or NaN components
+    let component = |value: f64| if value > 0.0 { value } else { 0.0 };
+    let (red, green, blue) = (component(color.red), component(color.green), component(color.blue));
+
+    let brightest = red.max(green).max(blue);
+    if brightest < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    let mut exponent = brightest.log2().floor() as i32 + 1;
+    if brightest / 2f64.powi(exponent) >= 1.0 {
+        exponent += 1;
+    }
+    if exponent > 127 {
+        return [255, 255, 255, 255];
+    }
+
+    // the brightest component gets a mantissa in 128..=255
+    let scale = 256.0 / 2f64.powi(exponent);
+    return [(red * scale) as u8, (green * scale) as u8, (blue * scale) as u8, (exponent + 128) as u8];
+}
+
+pub fn rgbe_to_color(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+
+    let scale = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+    return Color::new(rgbe[0] as f64 * scale, rgbe[1] as f64 * scale, rgbe[2] as f64 * scale);
+}
+
+impl Canvas {
+    // Radiance RGBE, run-length encoded whenever the width allows it
+    pub fn write_hdr<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", self.height(), self.width())?;
+
+        let mut scanline = Vec::with_capacity(self.width());
+        for y in 0..self.height() {
+            scanline.clear();
+            for x in 0..self.width() {
+                scanline.push(color_to_rgbe(self.pixel_at(x, y)));
+            }
+
+            if (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&self.width()) {
+                writer.write_all(&encode_rle_scanline(&scanline))?;
+            } else {
+                for rgbe in scanline.iter() {
+                    writer.write_all(rgbe)?;
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn from_hdr(hdr: &[u8]) -> Result<Canvas, String> {
+        let mut position = 0;
+        let mut next_line = || -> Option<String> {
+            let length = hdr[position..].iter().position(|byte| *byte == b'\n')?;
+            let line = String::from_utf8_lossy(&hdr[position..position + length]).into_owned();
+            position += length + 1;
+            return Some(line);
+        };
+
+        match next_line() {
+            Some(ref line) if line.starts_with("#?") => {}
+            _ => return Err("not a Radiance HDR file, expected the #? signature".to_string()),
+        }
+
+        // header lines run until the first blank one, EXPOSURE records a scale already applied to the pixels
+        let mut exposure = 1.0;
+        loop {
+            let line = next_line().ok_or("missing the blank line that ends the header")?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=") {
+                if format != "32-bit_rle_rgbe" {
+                    return Err(format!("unsupported pixel format '{}', expected 32-bit_rle_rgbe", format));
+                }
+            } else if let Some(value) = line.strip_prefix("EXPOSURE=") {
+                exposure *= value.trim().parse::<f64>()
+                    .ok().filter(|value| *value > 0.0)
+                    .ok_or(format!("invalid exposure '{}'", value.trim()))?;
+            }
+        }
+
+        let resolution = next_line().ok_or("missing the resolution line")?;
+        let (width, height) = parse_resolution(&resolution)?;
+
+        // checked before the canvas is allocated, so a short file can't claim a huge image
+        let mut pixels = &hdr[position..];
+        let size = width.checked_mul(height)
+            .ok_or(format!("image size {}x{} is too large", width, height))?;
+        let needed = height.checked_mul(min_scanline_bytes(width))
+            .ok_or(format!("image size {}x{} is too large", width, height))?;
+        if pixels.len() < needed {
+            return Err(format!("truncated pixel data, {} pixels need at least {} bytes but found {}", size, needed, pixels.len()));
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            let (scanline, rest) = decode_scanline(pixels, width)
+                .map_err(|why| format!("scanline {}: {}", y, why))?;
+            pixels = rest;
+
+            for (x, rgbe) in scanline.into_iter().enumerate() {
+                canvas.write_pixel(x, y, rgbe_to_color(rgbe) * (1.0 / exposure));
+            }
+        }
+
+        return Ok(canvas);
+    }
+}
+
+// only the standard top-to-bottom, left-to-right orientation is supported
+fn parse_resolution(line: &str) -> Result<(usize, usize), String> {
+    let unsupported = || format!("unsupported resolution line '{}', expected -Y height +X width", line);
+
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() != 4 || tokens[0] != "-Y" || tokens[2] != "+X" {
+        return Err(unsupported());
+    }
+    let height = tokens[1].parse::<usize>().map_err(|_| unsupported())?;
+    let width = tokens[3].parse::<usize>().map_err(|_| unsupported())?;
+
+    return Ok((width, height));
+}
+
+// the shortest possible scanline is one old-style pixel followed by repeat markers,
+// each holding the next 8 bits of the count, run-length encoding never does better
+fn min_scanline_bytes(width: usize) -> usize {
+    if width == 0 {
+        return 0;
+    }
+    let count_bits = (usize::BITS - (width - 1).leading_zeros()) as usize;
+
+    return 4 * (1 + count_bits.div_ceil(8));
+}
+
+// each channel is stored separately as runs (count above 128) and literal byte dumps
+fn encode_rle_scanline(scanline: &[[u8; 4]]) -> Vec<u8> {
+    let width = scanline.len();
+    let mut encoded = vec!(2, 2, (width >> 8) as u8, (width & 0xff) as u8);
+
+    for channel in 0..4 {
+        let data: Vec<u8> = scanline.iter().map(|rgbe| rgbe[channel]).collect();
+        let run_length = |start: usize| {
+            return data[start..].iter().take(127).take_while(|byte| **byte == data[start]).count();
+        };
+
+        let mut i = 0;
+        while i < width {
+            let run = run_length(i);
+            if run >= MIN_RUN_LENGTH {
+                encoded.push(128 + run as u8);
+                encoded.push(data[i]);
+                i += run;
+                continue;
+            }
+
+            let start = i;
+            while i < width && i - start < 128 && (i == start || run_length(i) < MIN_RUN_LENGTH) {
+                i += 1;
+            }
+            encoded.push((i - start) as u8);
+            encoded.extend_from_slice(&data[start..i]);
+        }
+    }
+
+    return encoded;
+}
+
+type Scanline<'a> = (Vec<[u8; 4]>, &'a [u8]);
+
+// returns the scanline's pixels and the bytes after it
+fn decode_scanline(bytes: &[u8], width: usize) -> Result<Scanline<'_>, String> {
+    let truncated = || "truncated pixel data".to_string();
+
+    let is_rle = (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&width)
+        && bytes.len() >= 4 && bytes[0] == 2 && bytes[1] == 2 && bytes[2] & 0x80 == 0;
+    if !is_rle {
+        return decode_flat_scanline(bytes, width);
+    }
+
+    let encoded_width = ((bytes[2] as usize) << 8) | bytes[3] as usize;
+    if encoded_width != width {
+        return Err(format!("run-length encoded width {} does not match the image width {}", encoded_width, width));
+    }
+
+    let mut scanline = vec!([0u8; 4]; width);
+    let mut position = 4;
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = *bytes.get(position).ok_or_else(truncated)? as usize;
+            position += 1;
+
+            if count > 128 {
+                let count = count - 128;
+                if x + count > width {
+                    return Err("run goes past the end of the scanline".to_string());
+                }
+                let value = *bytes.get(position).ok_or_else(truncated)?;
+                position += 1;
+                for pixel in scanline[x..x + count].iter_mut() {
+                    pixel[channel] = value;
+                }
+                x += count;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err(format!("invalid literal count {}", count));
+                }
+                let values = bytes.get(position..position + count).ok_or_else(truncated)?;
+                position += count;
+                for (pixel, value) in scanline[x..x + count].iter_mut().zip(values.iter()) {
+                    pixel[channel] = *value;
+                }
+                x += count;
+            }
+        }
+    }
+
+    return Ok((scanline, &bytes[position..]));
+}
+
+// uncompressed pixels, where a 1,1,1 pixel repeats the previous one (older Radiance files)
+fn decode_flat_scanline(bytes: &[u8], width: usize) -> Result<Scanline<'_>, String> {
+    let mut scanline: Vec<[u8; 4]> = Vec::with_capacity(width);
+    let mut position = 0;
+    let mut shift = 0;
+
+    while scanline.len() < width {
+        let rgbe = bytes.get(position..position + 4).ok_or("truncated pixel data")?;
+        position += 4;
+
+        if rgbe[0] == 1 && rgbe[1] == 1 && rgbe[2] == 1 {
+            let previous = *scanline.last().ok_or("repeat marker without a previous pixel")?;
+            // each further marker in a row adds the next 8 bits of the count, too many can only overflow it
+            let count = (rgbe[3] as usize).checked_shl(shift)
+                .filter(|count| *count <= width - scanline.len())
+                .ok_or("run goes past the end of the scanline")?;
+            scanline.extend(std::iter::repeat_n(previous, count));
+            shift += 8;
+        } else {
+            scanline.push([rgbe[0], rgbe[1], rgbe[2], rgbe[3]]);
+            shift = 0;
+        }
+    }
+
+    return Ok((scanline, &bytes[position..]));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hdr::{color_to_rgbe, rgbe_to_color, encode_rle_scanline};
+    use crate::canvas::Canvas;
+    use crate::canvas::tests::test_canvas;
+    use crate::color::Color;
+
+    macro_rules! rgbe_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (color, expected) = $value;
+
+                let rgbe = color_to_rgbe(color);
+
+                assert_eq!(expected, rgbe);
+                assert_eq!(color, rgbe_to_color(rgbe));
+            }
+        )*
+        }
+    }
+
+    rgbe_tests! {
+        rgbe_of_black: (Color::new(0.0, 0.0, 0.0), [0, 0, 0, 0]),
+        rgbe_of_white: (Color::new(1.0, 1.0, 1.0), [128, 128, 128, 129]),
+        rgbe_of_a_bright_color: (Color::new(4.0, 2.0, 1.0), [128, 64, 32, 131]),
+        rgbe_of_a_dim_color: (Color::new(0.25, 0.125, 0.0), [128, 64, 0, 127]),
+        rgbe_just_below_a_power_of_two: (Color::new(0.99609375, 0.5, 0.0), [255, 128, 0, 128]),
+    }
+
+    #[test]
+    fn rgbe_clamps_negative_and_nan_components_to_zero() {
+        assert_eq!([128, 0, 0, 129], color_to_rgbe(Color::new(1.0, -2.0, f64::NAN)));
+    }
+
+    #[test]
+    fn rgbe_keeps_about_two_significant_digits() {
+        let color = Color::new(123.456, 7.89, 0.5);
+
+        let back = rgbe_to_color(color_to_rgbe(color));
+
+        assert!((back.red - color.red).abs() / color.red < 1.0 / 128.0);
+        assert!((back.green - color.green).abs() < 123.456 / 128.0);
+    }
+
+    fn striped_canvas(width: usize, height: usize) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                // runs of equal pixels mixed with changing ones
+                let value = if x % 10 < 5 { 2.5 } else { (x * y) as f64 / 8.0 };
+                c.write_pixel(x, y, Color::new(value, 0.5, 16.0 - value));
+            }
+        }
+
+        return c;
+    }
+
+    fn assert_round_trip(c: &Canvas) {
+        let mut out = Vec::new();
+        c.write_hdr(&mut out).unwrap();
+
+        let back = Canvas::from_hdr(&out).unwrap();
+
+        assert_eq!((c.width(), c.height()), (back.width(), back.height()));
+        for y in 0..c.height() {
+            for x in 0..c.width() {
+                assert_eq!(rgbe_to_color(color_to_rgbe(c.pixel_at(x, y))), back.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn writing_a_radiance_header() {
+        let mut out = Vec::new();
+
+        Canvas::new(5, 3).write_hdr(&mut out).unwrap();
+
+        assert!(out.starts_with(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 3 +X 5\n"));
+    }
+
+    #[test]
+    fn narrow_images_are_written_uncompressed() {
+        let mut out = Vec::new();
+
+        test_canvas().write_hdr(&mut out).unwrap();
+
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 2\n";
+        assert_eq!(header.len() + 2 * 2 * 4, out.len());
+        assert_round_trip(&test_canvas());
+    }
+
+    #[test]
+    fn wide_images_are_run_length_encoded() {
+        let c = Canvas::new(300, 2);
+        let mut out = Vec::new();
+
+        c.write_hdr(&mut out).unwrap();
+
+        assert!(out.len() < 300 * 2 * 4 / 10);
+        assert_round_trip(&c);
+    }
+
+    #[test]
+    fn hdr_round_trip_keeps_values_above_one() {
+        let c = striped_canvas(200, 4);
+        let mut out = Vec::new();
+        c.write_hdr(&mut out).unwrap();
+
+        let back = Canvas::from_hdr(&out).unwrap();
+
+        assert_eq!(Color::new(2.5, 0.5, 13.5), back.pixel_at(0, 0));
+        assert_round_trip(&c);
+    }
+
+    #[test]
+    fn encoding_runs_and_literals() {
+        let mut scanline = vec!([7, 0, 0, 128]; 6);
+        scanline.extend(vec!([1, 0, 0, 128], [2, 0, 0, 128], [3, 0, 0, 128]));
+
+        let encoded = encode_rle_scanline(&scanline);
+
+        assert_eq!(vec!(2, 2, 0, 9, 134, 7, 3, 1, 2, 3), encoded[..10].to_vec());
+        assert_eq!(vec!(137, 0, 137, 0, 137, 128), encoded[10..].to_vec());
+    }
+
+    #[test]
+    fn long_literal_stretches_are_split() {
+        let scanline: Vec<[u8; 4]> = (0..300).map(|x| [(x % 256) as u8, 0, 0, 128]).collect();
+
+        let encoded = encode_rle_scanline(&scanline);
+
+        assert_eq!(128, encoded[4]);
+        assert_eq!(128, encoded[4 + 129]);
+        assert_eq!(44, encoded[4 + 2 * 129]);
+    }
+
+    #[test]
+    fn reading_applies_the_exposure() {
+        let hdr = b"#?RADIANCE\nEXPOSURE=2\nEXPOSURE=0.5e1\n\n-Y 1 +X 1\n\x80\x80\x80\x84";
+
+        let c = Canvas::from_hdr(hdr).unwrap();
+
+        assert_eq!(Color::new(0.8, 0.8, 0.8), c.pixel_at(0, 0));
+    }
+
+    #[test]
+    fn reading_old_style_repeated_pixels() {
+        let hdr = b"#?RGBE\n\n-Y 1 +X 5\n\x80\x40\x20\x81\x01\x01\x01\x03\x00\x00\x00\x00";
+
+        let c = Canvas::from_hdr(hdr).unwrap();
+
+        assert_eq!(Color::new(1.0, 0.5, 0.25), c.pixel_at(3, 0));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), c.pixel_at(4, 0));
+    }
+
+    #[test]
+    fn reading_repeats_longer_than_run_length_encoding_allows() {
+        // 1 + 255 and 1 + 43 pixels
+        let hdr = b"#?RGBE\n\n-Y 1 +X 300\n\x80\x40\x20\x81\x01\x01\x01\xff\x80\x80\x80\x81\x01\x01\x01\x2b";
+
+        let c = Canvas::from_hdr(hdr).unwrap();
+
+        assert_eq!(Color::new(1.0, 0.5, 0.25), c.pixel_at(255, 0));
+        assert_eq!(Color::new(1.0, 1.0, 1.0), c.pixel_at(299, 0));
+    }
+
+    #[test]
+    fn reading_chained_repeat_markers() {
+        // 1 + 0xe7 + (3 << 8) pixels
+        let hdr = b"#?RGBE\n\n-Y 1 +X 1000\n\x80\x40\x20\x81\x01\x01\x01\xe7\x01\x01\x01\x03";
+
+        let c = Canvas::from_hdr(hdr).unwrap();
+
+        assert_eq!(Color::new(1.0, 0.5, 0.25), c.pixel_at(999, 0));
+    }
+
+    macro_rules! malformed_hdr_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (hdr, expected): (&[u8], &str) = $value;
+
+                assert_eq!(Err(expected.to_string()), Canvas::from_hdr(hdr));
+            }
+        )*
+        }
+    }
+
+    malformed_hdr_tests! {
+        hdr_without_signature: (b"P6\n1 1\n255\n", "not a Radiance HDR file, expected the #? signature"),
+        hdr_with_xyz_pixels: (b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n", "unsupported pixel format '32-bit_rle_xyze', expected 32-bit_rle_rgbe"),
+        hdr_with_invalid_exposure: (b"#?RADIANCE\nEXPOSURE=-1\n\n", "invalid exposure '-1'"),
+        hdr_without_header_end: (b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n", "missing the blank line that ends the header"),
+        hdr_without_resolution: (b"#?RADIANCE\n\n", "missing the resolution line"),
+        hdr_with_flipped_orientation: (b"#?RADIANCE\n\n+Y 1 +X 1\n", "unsupported resolution line '+Y 1 +X 1', expected -Y height +X width"),
+        hdr_with_a_huge_image: (b"#?RADIANCE\n\n-Y 200000 +X 200000\n\x80\x80\x80\x81", "truncated pixel data, 40000000000 pixels need at least 3200000 bytes but found 4"),
+        hdr_with_an_overflowing_size: (b"#?RADIANCE\n\n-Y 2 +X 18446744073709551615\n", "image size 18446744073709551615x2 is too large"),
+        hdr_with_truncated_flat_pixels: (b"#?RADIANCE\n\n-Y 2 +X 3\n\x80\x80\x80\x81\x80\x80\x80\x81\x80\x80\x80\x81\x80\x80\x80\x81", "scanline 1: truncated pixel data"),
+        hdr_with_truncated_rle_pixels: (b"#?RADIANCE\n\n-Y 1 +X 8\n\x02\x02\x00\x08\x88\x01\x88\x01", "scanline 0: truncated pixel data"),
+        hdr_with_mismatched_rle_width: (b"#?RADIANCE\n\n-Y 1 +X 8\n\x02\x02\x00\x09\x00\x00\x00\x00", "scanline 0: run-length encoded width 9 does not match the image width 8"),
+        hdr_with_overlong_run: (b"#?RADIANCE\n\n-Y 1 +X 8\n\x02\x02\x00\x08\x89\x01\x00\x00", "scanline 0: run goes past the end of the scanline"),
+        hdr_with_zero_literal_count: (b"#?RADIANCE\n\n-Y 1 +X 8\n\x02\x02\x00\x08\x00\x00\x00\x00", "scanline 0: invalid literal count 0"),
+        hdr_with_too_many_repeat_markers: (b"#?RADIANCE\n\n-Y 1 +X 2\n\x80\x80\x80\x81\x01\x01\x01\x00\x01\x01\x01\x00\x01\x01\x01\x00\x01\x01\x01\x00\x01\x01\x01\x00\x01\x01\x01\x00\x01\x01\x01\x00\x01\x01\x01\x00\x01\x01\x01\x01", "scanline 0: run goes past the end of the scanline"),
+        hdr_repeat_without_previous_pixel: (b"#?RADIANCE\n\n-Y 1 +X 2\n\x01\x01\x01\x02\x80\x80\x80\x81", "scanline 0: repeat marker without a previous pixel"),
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::{self, Write};
+use std::path::Path;
+use crate::canvas::Canvas;
+use crate::ppm::PpmFormat;
+use crate::png_file::PngDepth;
+use crate::display::DisplayTransform;
+
+// the image formats a render can be saved as, chosen by the output file's extension
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Hdr,
+    Pfm,
+}
+
+impl ImageFormat {
+    pub fn from_file_name(file_name: &str) -> Result<ImageFormat, String> {
+        let extension = Path::new(file_name).extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase())
+            .ok_or(format!("'{}' has no file extension, expected .ppm, .png, .hdr or .pfm", file_name))?;
+
+        return match extension.as_str() {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "png" => Ok(ImageFormat::Png),
+            "hdr" => Ok(ImageFormat::Hdr),
+            "pfm" => Ok(ImageFormat::Pfm),
+            _ => Err(format!("unsupported image format '.{}', expected .ppm, .png, .hdr or .pfm", extension)),
+        };
+    }
+}
+
+impl Canvas {
+    // PPM and PNG get the display transform, the HDR formats keep the linear values
+    // only PNG can store alpha, and it does so when some pixel is not opaque
+    pub fn write_image<W: Write>(&self, writer: &mut W, format: ImageFormat, display: &DisplayTransform) -> io::Result<()> {
+        let ppm_format = if display.binary_ppm { PpmFormat::Binary } else { PpmFormat::Plain };
+        let png_depth = if display.sixteen_bit { PngDepth::Sixteen } else { PngDepth::Eight };
+
+        return match format {
+            ImageFormat::Ppm => self.to_display(display).write_ppm(writer, ppm_format, png_depth.maxval()),
+            ImageFormat::Png => self.to_display(display).write_png(writer, png_depth, !self.is_opaque()),
+            ImageFormat::Hdr => self.write_hdr(writer),
+            ImageFormat::Pfm => self.write_pfm(writer),
+        };
+    }
+
+    pub fn read_image(bytes: &[u8], format: ImageFormat) -> Result<Canvas, String> {
+        return match format {
+            ImageFormat::Ppm => Canvas::from_ppm(bytes),
+            ImageFormat::Png => Err("reading PNG images is not supported".to_string()),
+            ImageFormat::Hdr => Canvas::from_hdr(bytes),
+            ImageFormat::Pfm => Canvas::from_pfm(bytes),
+        };
+    }
+
+    // loads an image in the format its extension names
+    pub fn from_file(file_name: &str) -> Result<Canvas, String> {
+        let format = ImageFormat::from_file_name(file_name)
+            .map_err(|why| format!("couldn't load {}: {}", file_name, why))?;
+        let contents = std::fs::read(file_name)
+            .map_err(|why| format!("couldn't read {}: {}", file_name, why))?;
+
+        return Canvas::read_image(&contents, format)
+            .map_err(|why| format!("couldn't load {}: {}", file_name, why));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::image_file::ImageFormat;
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+    use crate::display::DisplayTransform;
+
+    macro_rules! image_format_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (file_name, expected) = $value;
+
+                assert_eq!(expected, ImageFormat::from_file_name(file_name));
+            }
+        )*
+        }
+    }
+
+    image_format_tests! {
+        image_format_from_ppm_extension: ("render.ppm", Ok(ImageFormat::Ppm)),
+        image_format_from_png_extension: ("out/render.png", Ok(ImageFormat::Png)),
+        image_format_from_hdr_extension: ("render.hdr", Ok(ImageFormat::Hdr)),
+        image_format_from_pfm_extension: ("render.pfm", Ok(ImageFormat::Pfm)),
+        image_format_extension_is_case_insensitive: ("C:/temp/RENDER.PNG", Ok(ImageFormat::Png)),
+        image_format_unknown_extension: ("render.jpg", Err("unsupported image format '.jpg', expected .ppm, .png, .hdr or .pfm".to_string())),
+        image_format_missing_extension: ("render", Err("'render' has no file extension, expected .ppm, .png, .hdr or .pfm".to_string())),
+    }
+
+    #[test]
+    fn writing_an_image_uses_the_chosen_format() {
+        let c = Canvas::new(2, 2);
+        let mut ppm = Vec::new();
+        let mut png = Vec::new();
+
+        c.write_image(&mut ppm, ImageFormat::Ppm, &DisplayTransform::default()).unwrap();
+        c.write_image(&mut png, ImageFormat::Png, &DisplayTransform::default()).unwrap();
+
+        assert!(ppm.starts_with(b"P3\n2 2\n255\n"));
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    #[test]
+    fn writing_a_16_bit_binary_ppm_and_a_16_bit_png() {
+        let c = Canvas::new(2, 2);
+        let display = DisplayTransform { sixteen_bit: true, binary_ppm: true, ..DisplayTransform::default() };
+        let mut ppm = Vec::new();
+        let mut png = Vec::new();
+
+        c.write_image(&mut ppm, ImageFormat::Ppm, &display).unwrap();
+        c.write_image(&mut png, ImageFormat::Png, &display).unwrap();
+
+        assert_eq!(b"P6\n2 2\n65535\n".len() + 2 * 2 * 3 * 2, ppm.len());
+        assert!(ppm.starts_with(b"P6\n2 2\n65535\n"));
+        // the bit depth byte of the IHDR chunk
+        assert_eq!(16, png[24]);
+    }
+
+    #[test]
+    fn eight_bit_images_go_through_the_display_transform() {
+        let mut c = Canvas::new(1, 1);
+        c.write_pixel(0, 0, Color::new(0.18, 2.0, 0.0));
+        let mut ppm = Vec::new();
+
+        c.write_image(&mut ppm, ImageFormat::Ppm, &DisplayTransform::default()).unwrap();
+
+        assert_eq!("P3\n1 1\n255\n118 255 0\n", String::from_utf8(ppm).unwrap());
+    }
+
+    #[test]
+    fn png_images_get_an_alpha_channel_when_needed() {
+        let mut c = Canvas::new(1, 1);
+        let mut opaque = Vec::new();
+        c.write_image(&mut opaque, ImageFormat::Png, &DisplayTransform::default()).unwrap();
+        c.write_alpha(0, 0, 0.5);
+        let mut translucent = Vec::new();
+
+        c.write_image(&mut translucent, ImageFormat::Png, &DisplayTransform::default()).unwrap();
+
+        // the color type byte of the IHDR chunk, 2 is RGB and 6 RGBA
+        assert_eq!(2, opaque[25]);
+        assert_eq!(6, translucent[25]);
+    }
+
+    // values above 1 would not survive tone mapping or clamping
+    macro_rules! image_round_trip_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let format = $value;
+                let mut c = Canvas::new(2, 1);
+                c.write_pixel(0, 0, Color::new(4.0, 0.5, 0.25));
+                let mut out = Vec::new();
+                c.write_image(&mut out, format, &DisplayTransform::default()).unwrap();
+
+                let back = Canvas::read_image(&out, format).unwrap();
+
+                assert_eq!(Color::new(4.0, 0.5, 0.25), back.pixel_at(0, 0).round());
+                assert_eq!(Color::new(0.0, 0.0, 0.0), back.pixel_at(1, 0));
+            }
+        )*
+        }
+    }
+
+    image_round_trip_tests! {
+        image_round_trip_hdr: ImageFormat::Hdr,
+        image_round_trip_pfm: ImageFormat::Pfm,
+    }
+
+    #[test]
+    fn reading_png_images_is_not_supported() {
+        assert_eq!(Err("reading PNG images is not supported".to_string()), Canvas::read_image(b"", ImageFormat::Png));
+    }
+
+    #[test]
+    fn loading_an_image_file_with_an_unknown_extension() {
+        let result = Canvas::from_file("texture.tga");
+
+        assert_eq!(Err("couldn't load texture.tga: unsupported image format '.tga', expected .ppm, .png, .hdr or .pfm".to_string()), result);
+    }
+}
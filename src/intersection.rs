@@ -1,19 +1,135 @@
 use std::ops::{Index, IndexMut};
-use crate::sphere::Sphere;
+use crate::ray::Ray;
+use crate::tuple::{Tuple, EPSILON};
+use crate::shape::Shape;
 
 #[derive(Debug)]
-#[derive(PartialEq)]
 #[derive(Copy, Clone)]
-pub struct Intersection {
+pub struct Intersection<'a> {
     pub t: f64,
-    pub object: Sphere,
+    pub object: &'a dyn Shape,
+    // where on the surface the hit happened, for shapes that interpolate across it (e.g. smooth triangles)
+    pub u: f64,
+    pub v: f64,
 }
 
-pub struct Intersections {
-    pub(crate) values: Vec<Intersection>,
+impl PartialEq for Intersection<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.t == other.t && self.object.id() == other.object.id();
+    }
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(t: f64, object: &'a dyn Shape) -> Self {
+        return Intersection { t, object, u: 0.0, v: 0.0 };
+    }
+
+    pub fn with_uv(t: f64, object: &'a dyn Shape, u: f64, v: f64) -> Self {
+        return Intersection { t, object, u, v };
+    }
+
+    // xs are all the intersections along the ray, needed to know which objects the hit is inside of
+    pub fn prepare_computations(&self, ray: &Ray, xs: &Intersections<'a>) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at_hit(point, self);
+        let inside = normalv.dot(eyev) < 0.0;
+
+        if inside {
+            normalv = -normalv;
+        }
+
+        let (n1, n2) = self.refractive_indices(xs);
+
+        return Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            eyev,
+            normalv,
+            inside,
+            over_point: point + normalv * EPSILON,
+            under_point: point - normalv * EPSILON,
+            reflectv: ray.direction.reflect(normalv),
+            n1,
+            n2,
+        };
+    }
+
+    // the refractive indices on either side of this hit, found by walking the intersections
+    // in order and tracking which objects the ray is currently inside of
+    fn refractive_indices(&self, xs: &Intersections<'a>) -> (f64, f64) {
+        let mut containers: Vec<&dyn Shape> = Vec::new();
+        let mut n1 = 1.0;
+
+        for i in xs.values.iter() {
+            if i == self {
+                n1 = containers.last().map_or(1.0, |object| object.material().refractive_index);
+            }
+
+            match containers.iter().position(|object| object.id() == i.object.id()) {
+                Some(index) => { containers.remove(index); }
+                None => containers.push(i.object),
+            }
+
+            if i == self {
+                let n2 = containers.last().map_or(1.0, |object| object.material().refractive_index);
+                return (n1, n2);
+            }
+        }
+
+        return (n1, 1.0);
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    pub point: Tuple,
+    pub eyev: Tuple,
+    pub normalv: Tuple,
+    pub inside: bool,
+    // the hit point nudged above the surface, so rays cast from it do not hit the surface itself
+    pub over_point: Tuple,
+    // the hit point nudged below the surface, where refracted rays start
+    pub under_point: Tuple,
+    // the incoming ray bounced off the surface, used to follow reflections
+    pub reflectv: Tuple,
+    // refractive indices of the material being exited and the one being entered
+    pub n1: f64,
+    pub n2: f64,
+}
+
+impl Computations<'_> {
+    // Schlick's approximation of the Fresnel effect, the fraction of light reflected at the hit
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(self.normalv);
+
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+            if sin2_t > 1.0 {
+                // total internal reflection
+                return 1.0;
+            }
+
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+
+        return r0 + (1.0 - r0) * (1.0 - cos).powi(5);
+    }
+}
+
+pub struct Intersections<'a> {
+    pub(crate) values: Vec<Intersection<'a>>,
 }
 
-impl Intersections {
+impl<'a> Intersections<'a> {
     pub fn new() -> Self {
         return Intersections { values: Vec::new() };
     }
@@ -23,22 +139,22 @@ impl Intersections {
         return self.values.len();
     }
 
-    pub fn hit(&self) -> Option<Intersection> {
+    pub fn hit(&self) -> Option<Intersection<'a>> {
         return self.values.clone().into_iter()
             .filter(|f| f.t > 0.0)
             .min_by(|x, y| x.t.partial_cmp(&y.t).unwrap());
     }
 }
 
-impl Index<usize> for Intersections {
-    type Output = Intersection;
+impl<'a> Index<usize> for Intersections<'a> {
+    type Output = Intersection<'a>;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.values[index]
     }
 }
 
-impl IndexMut<usize> for Intersections {
+impl IndexMut<usize> for Intersections<'_> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.values[index]
     }
@@ -49,33 +165,40 @@ mod tests {
     use crate::intersection::{Intersection, Intersections};
     use crate::sphere::Sphere;
     use crate::ray::Ray;
-    use crate::tuple::Tuple;
+    use crate::tuple::{Tuple, EPSILON};
+    use crate::matrix::Matrix4;
+    use crate::shape::Shape;
+    use crate::plane::Plane;
+    use crate::material::Material;
+    use crate::sphere::tests::glass_sphere;
+    use std::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
 
     #[test]
     fn an_intersection_encapsulates_t_and_object() {
         let s = Sphere::new();
 
-        let i = Intersection {
-            t: 3.5,
-            object: s,
-        };
+        let i = Intersection::new(3.5, &s);
 
         assert_eq!(3.5, i.t);
-        assert_eq!(s, i.object)
+        assert_eq!(s.id, i.object.id())
+    }
+
+    #[test]
+    fn an_intersection_can_encapsulate_u_and_v() {
+        let s = Sphere::new();
+
+        let i = Intersection::with_uv(3.5, &s, 0.2, 0.4);
+
+        assert_eq!(0.2, i.u);
+        assert_eq!(0.4, i.v);
     }
 
     #[test]
     fn aggregating_intersections() {
         let s = Sphere::new();
-        let i1 = Intersection {
-            t: 1.0,
-            object: s,
-        };
+        let i1 = Intersection::new(1.0, &s);
 
-        let i2 = Intersection {
-            t: 2.0,
-            object: s,
-        };
+        let i2 = Intersection::new(2.0, &s);
 
         let xs = Intersections { values: vec!(i1, i2) };
 
@@ -95,18 +218,18 @@ mod tests {
 
         let s = Sphere::new();
 
-        let result = ray.intersect(s);
+        let result = ray.intersect(&s);
 
         assert_eq!(2, result.len());
-        assert_eq!(s, result[0].object);
-        assert_eq!(s, result[0].object);
+        assert_eq!(s.id, result[0].object.id());
+        assert_eq!(s.id, result[1].object.id());
     }
 
     #[test]
     fn the_hit_when_all_interactions_have_positive_t() {
         let s = Sphere::new();
-        let i1 = Intersection { t: 1.0, object: s };
-        let i2 = Intersection { t: 2.0, object: s };
+        let i1 = Intersection::new(1.0, &s);
+        let i2 = Intersection::new(2.0, &s);
 
         let xs = Intersections { values: vec!(i1, i2) };
 
@@ -118,8 +241,8 @@ mod tests {
     #[test]
     fn the_hit_when_some_interactions_have_negative_t() {
         let s = Sphere::new();
-        let i1 = Intersection { t: -1.0, object: s };
-        let i2 = Intersection { t: 1.0, object: s };
+        let i1 = Intersection::new(-1.0, &s);
+        let i2 = Intersection::new(1.0, &s);
 
         let xs = Intersections { values: vec!(i1, i2) };
 
@@ -131,8 +254,8 @@ mod tests {
     #[test]
     fn the_hit_when_all_interactions_have_negative_t() {
         let s = Sphere::new();
-        let i1 = Intersection { t: -2.0, object: s };
-        let i2 = Intersection { t: -1.0, object: s };
+        let i1 = Intersection::new(-2.0, &s);
+        let i2 = Intersection::new(-1.0, &s);
 
         let xs = Intersections { values: vec!(i1, i2) };
 
@@ -144,10 +267,10 @@ mod tests {
     #[test]
     fn the_hit_is_always_the_lowest_nonnegative_intersection() {
         let s = Sphere::new();
-        let i1 = Intersection { t: 5.0, object: s };
-        let i2 = Intersection { t: 7.0, object: s };
-        let i3 = Intersection { t: -3.0, object: s };
-        let i4 = Intersection { t: 2.0, object: s };
+        let i1 = Intersection::new(5.0, &s);
+        let i2 = Intersection::new(7.0, &s);
+        let i3 = Intersection::new(-3.0, &s);
+        let i4 = Intersection::new(2.0, &s);
 
         let xs = Intersections { values: vec!(i1, i2, i3, i4) };
 
@@ -155,4 +278,196 @@ mod tests {
 
         assert_eq!(Some(i4), actual);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn precomputing_the_state_of_an_intersection() {
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let shape = Sphere::new();
+        let i = Intersection::new(4.0, &shape);
+
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        assert_eq!(i.t, comps.t);
+        assert_eq!(i.object.id(), comps.object.id());
+        assert_eq!(Tuple::point(0.0, 0.0, -1.0), comps.point);
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), comps.eyev);
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), comps.normalv);
+    }
+
+    #[test]
+    fn the_hit_when_an_intersection_occurs_on_the_outside() {
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let shape = Sphere::new();
+        let i = Intersection::new(4.0, &shape);
+
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn the_hit_when_an_intersection_occurs_on_the_inside() {
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let shape = Sphere::new();
+        let i = Intersection::new(1.0, &shape);
+
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        assert_eq!(Tuple::point(0.0, 0.0, 1.0), comps.point);
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), comps.eyev);
+        assert!(comps.inside);
+        // normal would have been (0, 0, 1), but is inverted
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), comps.normalv);
+    }
+
+    #[test]
+    fn the_hit_should_offset_the_point() {
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let mut shape = Sphere::new();
+        shape.set_transform(Matrix4::translation(0.0, 0.0, 1.0));
+        let i = Intersection::new(5.0, &shape);
+
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        assert!(comps.over_point.z < -EPSILON / 2.0);
+        assert!(comps.point.z > comps.over_point.z);
+    }
+
+    #[test]
+    fn precomputing_the_reflection_vector() {
+        let shape = Plane::new();
+        let r = Ray {
+            origin: Tuple::point(0.0, 1.0, -1.0),
+            direction: Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let i = Intersection::new(SQRT_2, &shape);
+
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        assert_eq!(Tuple::vector(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2), comps.reflectv);
+    }
+
+    macro_rules! finding_n1_and_n2_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (index, n1, n2) = $value;
+
+                let mut a = glass_sphere();
+                a.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+                a.set_material(Material { refractive_index: 1.5, ..a.material().clone() });
+                let mut b = glass_sphere();
+                b.set_transform(Matrix4::translation(0.0, 0.0, -0.25));
+                b.set_material(Material { refractive_index: 2.0, ..b.material().clone() });
+                let mut c = glass_sphere();
+                c.set_transform(Matrix4::translation(0.0, 0.0, 0.25));
+                c.set_material(Material { refractive_index: 2.5, ..c.material().clone() });
+                let r = Ray {
+                    origin: Tuple::point(0.0, 0.0, -4.0),
+                    direction: Tuple::vector(0.0, 0.0, 1.0),
+                };
+                let xs = Intersections {
+                    values: vec!(
+                        Intersection::new(2.0, &a),
+                        Intersection::new(2.75, &b),
+                        Intersection::new(3.25, &c),
+                        Intersection::new(4.75, &b),
+                        Intersection::new(5.25, &c),
+                        Intersection::new(6.0, &a),
+                    )
+                };
+
+                let comps = xs[index].prepare_computations(&r, &xs);
+
+                assert_eq!(n1, comps.n1);
+                assert_eq!(n2, comps.n2);
+            }
+        )*
+        }
+    }
+
+    finding_n1_and_n2_tests! {
+        finding_n1_and_n2_at_0: (0, 1.0, 1.5),
+        finding_n1_and_n2_at_1: (1, 1.5, 2.0),
+        finding_n1_and_n2_at_2: (2, 2.0, 2.5),
+        finding_n1_and_n2_at_3: (3, 2.5, 2.5),
+        finding_n1_and_n2_at_4: (4, 2.5, 1.5),
+        finding_n1_and_n2_at_5: (5, 1.5, 1.0),
+    }
+
+    #[test]
+    fn the_under_point_is_offset_below_the_surface() {
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let mut shape = glass_sphere();
+        shape.set_transform(Matrix4::translation(0.0, 0.0, 1.0));
+        let i = Intersection::new(5.0, &shape);
+        let xs = Intersections { values: vec!(i) };
+
+        let comps = i.prepare_computations(&r, &xs);
+
+        assert!(comps.under_point.z > EPSILON / 2.0);
+        assert!(comps.point.z < comps.under_point.z);
+    }
+
+    #[test]
+    fn the_schlick_approximation_under_total_internal_reflection() {
+        let shape = glass_sphere();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, FRAC_1_SQRT_2),
+            direction: Tuple::vector(0.0, 1.0, 0.0),
+        };
+        let xs = Intersections {
+            values: vec!(Intersection::new(-FRAC_1_SQRT_2, &shape), Intersection::new(FRAC_1_SQRT_2, &shape))
+        };
+
+        let comps = xs[1].prepare_computations(&r, &xs);
+
+        assert_eq!(1.0, comps.schlick());
+    }
+
+    #[test]
+    fn the_schlick_approximation_with_a_perpendicular_viewing_angle() {
+        let shape = glass_sphere();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 1.0, 0.0),
+        };
+        let xs = Intersections {
+            values: vec!(Intersection::new(-1.0, &shape), Intersection::new(1.0, &shape))
+        };
+
+        let comps = xs[1].prepare_computations(&r, &xs);
+
+        assert_eq!(0.04, (comps.schlick() * 100000.0).round() / 100000.0);
+    }
+
+    #[test]
+    fn the_schlick_approximation_with_small_angle_and_n2_greater_than_n1() {
+        let shape = glass_sphere();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.99, -2.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let xs = Intersections { values: vec!(Intersection::new(1.8589, &shape)) };
+
+        let comps = xs[0].prepare_computations(&r, &xs);
+
+        assert_eq!(0.48873, (comps.schlick() * 100000.0).round() / 100000.0);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::color::Color;
+use crate::tuple::Tuple;
+use crate::material::Material;
+use crate::shape::Shape;
+
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub struct PointLight {
+    pub position: Tuple,
+    pub intensity: Color,
+}
+
+pub fn lighting(material: &Material, object: &dyn Shape, light: &PointLight, point: Tuple, eyev: Tuple,
+                normalv: Tuple, in_shadow: bool) -> Color {
+    let color = match &material.pattern {
+        Some(pattern) => pattern.pattern_at_shape(object, point),
+        None => material.color,
+    };
+    let effective_color = color * light.intensity;
+    let lightv = (light.position - point).normalize();
+    let ambient = effective_color * material.ambient;
+
+    if in_shadow {
+        return ambient;
+    }
+
+    // a negative value means the light is on the other side of the surface
+    let light_dot_normal = lightv.dot(normalv);
+    if light_dot_normal < 0.0 {
+        return ambient;
+    }
+
+    let diffuse = effective_color * material.diffuse * light_dot_normal;
+
+    // a negative value means the light reflects away from the eye
+    let reflectv = (-lightv).reflect(normalv);
+    let reflect_dot_eye = reflectv.dot(eyev);
+    let specular = if reflect_dot_eye <= 0.0 {
+        Color::default()
+    } else {
+        let factor = reflect_dot_eye.powf(material.shininess);
+        light.intensity * material.specular * factor
+    };
+
+    return ambient + diffuse + specular;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::light::{PointLight, lighting};
+    use crate::color::Color;
+    use crate::tuple::Tuple;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::pattern::StripePattern;
+    use std::rc::Rc;
+
+    #[test]
+    fn a_point_light_has_a_position_and_intensity() {
+        let intensity = Color::new(1.0, 1.0, 1.0);
+        let position = Tuple::point(0.0, 0.0, 0.0);
+
+        let light = PointLight { position, intensity };
+
+        assert_eq!(position, light.position);
+        assert_eq!(intensity, light.intensity);
+    }
+
+    macro_rules! lighting_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (eyev, normalv, light_position, expected) = $value;
+
+                let m = Material::default();
+                let position = Tuple::point(0.0, 0.0, 0.0);
+                let light = PointLight {
+                    position: light_position,
+                    intensity: Color::new(1.0, 1.0, 1.0),
+                };
+
+                let result = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, false);
+
+                assert_eq!(expected, result.round());
+            }
+        )*
+        }
+    }
+
+    lighting_tests! {
+        lighting_with_the_eye_between_the_light_and_the_surface: (
+            Tuple::vector(0.0, 0.0, -1.0), Tuple::vector(0.0, 0.0, -1.0),
+            Tuple::point(0.0, 0.0, -10.0), Color::new(1.9, 1.9, 1.9)),
+        lighting_with_the_eye_between_light_and_surface_eye_offset_45: (
+            Tuple::vector(0.0, 2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0), Tuple::vector(0.0, 0.0, -1.0),
+            Tuple::point(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0)),
+        lighting_with_eye_opposite_surface_light_offset_45: (
+            Tuple::vector(0.0, 0.0, -1.0), Tuple::vector(0.0, 0.0, -1.0),
+            Tuple::point(0.0, 10.0, -10.0), Color::new(0.73640, 0.73640, 0.73640)),
+        lighting_with_eye_in_the_path_of_the_reflection_vector: (
+            Tuple::vector(0.0, -2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0), Tuple::vector(0.0, 0.0, -1.0),
+            Tuple::point(0.0, 10.0, -10.0), Color::new(1.6364, 1.6364, 1.6364)),
+        lighting_with_the_light_behind_the_surface: (
+            Tuple::vector(0.0, 0.0, -1.0), Tuple::vector(0.0, 0.0, -1.0),
+            Tuple::point(0.0, 0.0, 10.0), Color::new(0.1, 0.1, 0.1)),
+    }
+
+    #[test]
+    fn lighting_with_the_surface_in_shadow() {
+        let m = Material::default();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight {
+            position: Tuple::point(0.0, 0.0, -10.0),
+            intensity: Color::new(1.0, 1.0, 1.0),
+        };
+
+        let result = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, true);
+
+        assert_eq!(Color::new(0.1, 0.1, 0.1), result.round());
+    }
+
+    #[test]
+    fn lighting_with_a_pattern_applied() {
+        let m = Material {
+            pattern: Some(Rc::new(StripePattern::new(Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0)))),
+            ambient: 1.0,
+            diffuse: 0.0,
+            specular: 0.0,
+            ..Material::default()
+        };
+        let object = Sphere::new();
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight {
+            position: Tuple::point(0.0, 0.0, -10.0),
+            intensity: Color::new(1.0, 1.0, 1.0),
+        };
+
+        let c1 = lighting(&m, &object, &light, Tuple::point(0.9, 0.0, 0.0), eyev, normalv, false);
+        let c2 = lighting(&m, &object, &light, Tuple::point(1.1, 0.0, 0.0), eyev, normalv, false);
+
+        assert_eq!(Color::new(1.0, 1.0, 1.0), c1);
+        assert_eq!(Color::new(0.0, 0.0, 0.0), c2);
+    }
+}
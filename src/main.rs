@@ -1,92 +1,176 @@
+#![allow(clippy::needless_return)]
+
 use crate::tuple::Tuple;
 use crate::canvas::Canvas;
+use crate::image_file::ImageFormat;
+use crate::display::DisplayTransform;
 use crate::color::Color;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 use crate::sphere::Sphere;
-use crate::ray::Ray;
+use crate::plane::Plane;
+use crate::group::Group;
+use crate::pattern::{Pattern, StripePattern, CheckersPattern, BlendPattern, PerturbedPattern};
+use crate::texture::{TextureMapPattern, UvCheckers, UvMapping};
+use std::rc::Rc;
+use crate::shape::Shape;
+use crate::camera::Camera;
 use crate::matrix::Matrix4;
 use std::f64::consts::PI;
+use crate::material::Material;
+use crate::light::PointLight;
+use crate::world::World;
+use crate::scene::Scene;
 
 mod tuple;
 mod projectile;
 mod environment;
 mod color;
 mod canvas;
+mod canvas_operations;
+mod ppm;
+mod png_file;
+mod hdr;
+mod pfm;
+mod display;
+mod image_file;
 mod matrix;
 mod matrix_transformations;
 mod ray;
+mod shape;
 mod sphere;
+mod plane;
+mod cube;
+mod cylinder;
+mod cone;
+mod triangle;
+mod smooth_triangle;
+mod group;
+mod csg;
+mod obj_file;
 mod intersection;
-
-fn write_position(c: &mut Canvas, t: Tuple) {
-    let color = Color { red: 1.0, green: 0.0, blue: 0.0 };
-
-    let round_x = t.x as usize;
-    let round_y = t.y as usize;
-    let fixed_y = c.height() - round_y;
-
-    if round_x >= c.width() || fixed_y >= c.height() {
-        println!("Position {:?} - is outside of canvas!!", t);
-    } else {
-        c.write_pixel(round_x, fixed_y, color);
-    }
-}
-
-fn save_file(c: &mut Canvas, file_name: &str) {
+mod material;
+mod light;
+mod noise;
+mod pattern;
+mod texture;
+mod world;
+mod camera;
+mod scene;
+
+fn save_file(c: &mut Canvas, file_name: &str, format: ImageFormat, transform: &DisplayTransform) {
     println!("Start creating file");
-    let ppm = c.to_ppm();
 
     let path = Path::new(file_name);
     let display = path.display();
 
-    let mut file = std::fs::File::create(&path).expect("create failed");
+    let file = std::fs::File::create(path).expect("create failed");
+    let mut writer = BufWriter::new(file);
 
-    match file.write_all(ppm.as_bytes()) {
+    match c.write_image(&mut writer, format, transform).and_then(|_| writer.flush()) {
         Err(why) => panic!("couldn't write to {}: {}", display, why),
         Ok(_) => println!("successfully wrote to {}", display),
     }
 }
 
 
-fn main() {
-    let canvas_pixels = 100;
+// the scene rendered when no scene file is given
+fn default_scene() -> (Camera, World) {
+    let mut floor = Plane::new();
+    floor.set_material(Material {
+        pattern: Some(Rc::new(CheckersPattern::new(Color::new(1.0, 0.9, 0.9), Color::new(0.5, 0.45, 0.45)))),
+        specular: 0.0,
+        reflective: 0.2,
+        ..Material::default()
+    });
 
-    let mut c = Canvas::new(canvas_pixels, canvas_pixels);
-    let mut shape = Sphere::new();
-    shape.set_transform(Matrix4::identity()
+    let mut left = Sphere::new();
+    left.set_transform(Matrix4::identity()
         .scale(0.5, 1.0, 1.0)
         .rotate_z(PI / 4.0)
+        .translate(-1.5, 1.0, 0.5)
     );
-    let ray_origin = Tuple::point(0.0, 0.0, -5.0);
-    let wall_z: f64 = 10.0;
-    let wall_size = 7.0;
-    let pixel_size: f64 = wall_size / canvas_pixels as f64;
-    let half = wall_size / 2.0;
-
-    for y in 0..canvas_pixels {
-        let y_64 = y as f64;
-        let world_y = half - pixel_size * y_64;
-
-        for x in 0..canvas_pixels {
-            let x_f64 = x as f64;
-            let world_x = -half + pixel_size * x_f64;
-
-            let position = Tuple::point(world_x, world_y, wall_z);
-            let direction = (position - ray_origin).normalize();
-
-            let r = Ray {
-                origin: ray_origin,
-                direction,
-            };
-            let xs = r.intersect(shape);
-            let result = xs.hit();
-            if result.is_some() {
-                write_position(&mut c, Tuple::point(x_f64, y_64, 0.0));
+    let checkers = UvCheckers::new(16.0, 8.0, Color::new(1.0, 0.2, 1.0), Color::new(0.5, 0.1, 0.5));
+    left.set_material(Material {
+        pattern: Some(Rc::new(TextureMapPattern::new(Rc::new(checkers), UvMapping::Spherical))),
+        ..Material::default()
+    });
+
+    let mut middle = Sphere::new();
+    middle.set_transform(Matrix4::translation(0.0, 1.0, 0.5));
+    let mut stripes = StripePattern::new(Color::new(0.1, 1.0, 0.5), Color::new(0.05, 0.5, 0.25));
+    stripes.set_transform(Matrix4::scaling(0.2, 0.2, 0.2).rotate_z(PI / 4.0));
+    middle.set_material(Material {
+        pattern: Some(Rc::new(stripes)),
+        diffuse: 0.7,
+        specular: 0.3,
+        ..Material::default()
+    });
+
+    let mut right = Sphere::new();
+    right.set_transform(Matrix4::scaling(0.5, 0.5, 0.5).translate(1.5, 0.5, -0.5));
+    let mut bands = StripePattern::new(Color::new(0.5, 1.0, 0.1), Color::new(0.25, 0.5, 0.05));
+    bands.set_transform(Matrix4::scaling(0.25, 0.25, 0.25));
+    let mut cross_bands = bands.clone();
+    cross_bands.set_transform(Matrix4::scaling(0.25, 0.25, 0.25).rotate_y(PI / 2.0));
+    let marble = BlendPattern::new(Rc::new(bands), Rc::new(cross_bands));
+    right.set_material(Material {
+        pattern: Some(Rc::new(PerturbedPattern::new(Rc::new(marble), 0.3))),
+        diffuse: 0.7,
+        specular: 0.3,
+        ..Material::default()
+    });
+
+    let mut spheres = Group::new();
+    spheres.add_child(Box::new(left));
+    spheres.add_child(Box::new(middle));
+    spheres.add_child(Box::new(right));
+    spheres.set_transform(Matrix4::rotation_y(PI / 8.0));
+
+    let mut world = World::new();
+    world.objects = vec!(Box::new(floor), Box::new(spheres));
+    world.lights.push(PointLight {
+        position: Tuple::point(-10.0, 10.0, -10.0),
+        intensity: Color::new(1.0, 1.0, 1.0),
+    });
+
+    let mut camera = Camera::new(100, 50, PI / 3.0);
+    camera.set_transform(Matrix4::view_transform(
+        Tuple::point(0.0, 1.5, -5.0),
+        Tuple::point(0.0, 1.0, 0.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    ));
+
+    return (camera, world);
+}
+
+// usage: rust_ray_tracing [scene.yaml [output.ppm]]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    let (camera, world, display) = match args.get(1) {
+        Some(scene_file) => match Scene::from_file(scene_file) {
+            Ok(scene) => (scene.camera, scene.world, scene.display),
+            Err(why) => {
+                eprintln!("{}", why);
+                std::process::exit(1);
             }
+        },
+        None => {
+            let (camera, world) = default_scene();
+            (camera, world, DisplayTransform::default())
         }
-    }
+    };
+    let output = args.get(2).map_or("c:/temp/sphere1.ppm", |file_name| file_name.as_str());
+    let format = match ImageFormat::from_file_name(output) {
+        Ok(format) => format,
+        Err(why) => {
+            eprintln!("{}", why);
+            std::process::exit(1);
+        }
+    };
 
-    save_file(&mut c, "c:/temp/sphere1.ppm")
-}
+    let mut c = camera.render(&world);
 
+    save_file(&mut c, output, format, &display)
+}
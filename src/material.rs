@@ -0,0 +1,96 @@
+use std::rc::Rc;
+use crate::color::Color;
+use crate::pattern::Pattern;
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Material {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    // 0 is not reflective at all, 1 is a perfect mirror
+    pub reflective: f64,
+    // 0 is opaque, 1 lets all light through
+    pub transparency: f64,
+    // how much light bends entering the material, 1 for vacuum, 1.5 for glass
+    pub refractive_index: f64,
+    // when set, used instead of the flat color
+    pub pattern: Option<Rc<dyn Pattern>>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            color: Color::new(1.0, 1.0, 1.0),
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+            pattern: None,
+        }
+    }
+}
+
+// patterns are compared by identity, two materials only match if they share the same pattern
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        let same_pattern = match (&self.pattern, &other.pattern) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        };
+
+        return same_pattern &&
+            self.color == other.color &&
+            self.ambient == other.ambient &&
+            self.diffuse == other.diffuse &&
+            self.specular == other.specular &&
+            self.shininess == other.shininess &&
+            self.reflective == other.reflective &&
+            self.transparency == other.transparency &&
+            self.refractive_index == other.refractive_index;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::material::Material;
+    use crate::color::Color;
+    use crate::pattern::StripePattern;
+
+    #[test]
+    fn the_default_material() {
+        let m = Material::default();
+
+        assert_eq!(Color::new(1.0, 1.0, 1.0), m.color);
+        assert_eq!(0.1, m.ambient);
+        assert_eq!(0.9, m.diffuse);
+        assert_eq!(0.9, m.specular);
+        assert_eq!(200.0, m.shininess);
+        assert_eq!(0.0, m.reflective);
+        assert_eq!(0.0, m.transparency);
+        assert_eq!(1.0, m.refractive_index);
+        assert!(m.pattern.is_none());
+    }
+
+    #[test]
+    fn materials_are_equal_only_when_sharing_the_same_pattern() {
+        let stripes = Rc::new(StripePattern::new(Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0)));
+        let m1 = Material { pattern: Some(stripes.clone()), ..Material::default() };
+        let m2 = Material { pattern: Some(stripes), ..Material::default() };
+        let m3 = Material {
+            pattern: Some(Rc::new(StripePattern::new(Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0)))),
+            ..Material::default()
+        };
+
+        assert_eq!(m1, m2);
+        assert_ne!(m1, m3);
+        assert_ne!(m1, Material::default());
+    }
+}
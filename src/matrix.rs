@@ -172,7 +172,7 @@ macro_rules! submatrix {
                 return self.minor(row, col);
             }
 
-            return self.minor(row, col) * -1.0;
+            return -self.minor(row, col);
         }
 
         pub fn determinant(self) -> f64 {
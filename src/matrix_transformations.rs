@@ -1,10 +1,13 @@
 use crate::matrix::Matrix4;
+use crate::tuple::Tuple;
 
 impl Matrix4 {
     pub fn inverse(self) -> Matrix4 {
         let mut result: [[f64; 4]; 4] = Default::default();
         let determinant = self.determinant();
 
+        // the cofactors are written transposed, so the loops index rather than iterate
+        #[allow(clippy::needless_range_loop)]
         for row in 0..4 {
             for col in 0..4 {
                 result[col][row] = self.cofactor(row, col) / determinant;
@@ -94,6 +97,25 @@ impl Matrix4 {
             [0.0, 0.0, 0.0, 1.0]
         ]);
     }
+
+    pub fn shear(self, xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix4 {
+        return Matrix4::shearing(xy, xz, yx, yz, zx, zy) * self;
+    }
+
+    pub fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> Matrix4 {
+        let forward = (to - from).normalize();
+        let left = forward.cross(up.normalize());
+        let true_up = left.cross(forward);
+
+        let orientation = Matrix4::new([
+            [left.x, left.y, left.z, 0.0],
+            [true_up.x, true_up.y, true_up.z, 0.0],
+            [-forward.x, -forward.y, -forward.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0]
+        ]);
+
+        return orientation * Matrix4::translation(-from.x, -from.y, -from.z);
+    }
 }
 
 #[cfg(test)]
@@ -423,4 +445,65 @@ mod tests {
         let result = t * p;
         assert_eq!(Tuple::point(10.0, 0.0, 2.0), result.round());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn the_transformation_matrix_for_the_default_orientation() {
+        let from = Tuple::point(0.0, 0.0, 0.0);
+        let to = Tuple::point(0.0, 0.0, -1.0);
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+
+        let t = Matrix4::view_transform(from, to, up);
+
+        assert_eq!(Matrix4::identity(), t);
+    }
+
+    #[test]
+    fn a_view_transformation_matrix_looking_in_positive_z_direction() {
+        let from = Tuple::point(0.0, 0.0, 0.0);
+        let to = Tuple::point(0.0, 0.0, 1.0);
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+
+        let t = Matrix4::view_transform(from, to, up);
+
+        assert_eq!(Matrix4::scaling(-1.0, 1.0, -1.0), t);
+    }
+
+    #[test]
+    fn the_view_transformation_moves_the_world() {
+        let from = Tuple::point(0.0, 0.0, 8.0);
+        let to = Tuple::point(0.0, 0.0, 0.0);
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+
+        let t = Matrix4::view_transform(from, to, up);
+
+        assert_eq!(Matrix4::translation(0.0, 0.0, -8.0), t);
+    }
+
+    #[test]
+    fn an_arbitrary_view_transformation() {
+        let from = Tuple::point(1.0, 3.0, 2.0);
+        let to = Tuple::point(4.0, -2.0, 8.0);
+        let up = Tuple::vector(1.0, 1.0, 0.0);
+
+        let t = Matrix4::view_transform(from, to, up);
+
+        let expected = Matrix4::new([
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.00000],
+            [0.00000, 0.00000, 0.00000, 1.00000]
+        ]);
+        assert_eq!(expected, t.round());
+    }
+
+    #[test]
+    fn fluent_shearing_is_applied_after_earlier_transformations() {
+        let p = Tuple::point(1.0, 1.0, 1.0);
+
+        let transform = Matrix4::identity()
+            .translate(1.0, 0.0, 0.0)
+            .shear(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+
+        assert_eq!(Tuple::point(3.0, 1.0, 1.0), transform * p);
+    }
+}
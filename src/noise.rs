@@ -0,0 +1,126 @@
+// Ken Perlin's improved noise (2002): smooth, repeatable 3D noise in the range -1..1
+
+const PERMUTATION: [usize; 256] = [
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225,
+    140, 36, 103, 30, 69, 142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148,
+    247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219, 203, 117, 35, 11, 32,
+    57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122,
+    60, 211, 133, 230, 220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54,
+    65, 25, 63, 161, 1, 216, 80, 73, 209, 76, 132, 187, 208, 89, 18, 169,
+    200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173, 186, 3, 64,
+    52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212,
+    207, 206, 59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213,
+    119, 248, 152, 2, 44, 154, 163, 70, 221, 153, 101, 155, 167, 43, 172, 9,
+    129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232, 178, 185, 112, 104,
+    218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162, 241,
+    81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157,
+    184, 84, 204, 176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93,
+    222, 114, 67, 29, 24, 72, 243, 141, 128, 195, 78, 66, 215, 61, 156, 180,
+];
+
+fn perm(index: usize) -> usize {
+    return PERMUTATION[index % 256];
+}
+
+fn fade(t: f64) -> f64 {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    return a + t * (b - a);
+}
+
+// the dot product of (x, y, z) with one of 12 gradient directions picked by the hash
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+
+    let u = if h & 1 == 0 { u } else { -u };
+    let v = if h & 2 == 0 { v } else { -v };
+
+    return u + v;
+}
+
+pub fn perlin_noise(x: f64, y: f64, z: f64) -> f64 {
+    // the unit cube containing the point, wrapped to the permutation table
+    let xi = (x.floor() as i64).rem_euclid(256) as usize;
+    let yi = (y.floor() as i64).rem_euclid(256) as usize;
+    let zi = (z.floor() as i64).rem_euclid(256) as usize;
+
+    // the point's position inside that cube
+    let x = x - x.floor();
+    let y = y - y.floor();
+    let z = z - z.floor();
+
+    let u = fade(x);
+    let v = fade(y);
+    let w = fade(z);
+
+    let a = perm(xi) + yi;
+    let aa = perm(a) + zi;
+    let ab = perm(a + 1) + zi;
+    let b = perm(xi + 1) + yi;
+    let ba = perm(b) + zi;
+    let bb = perm(b + 1) + zi;
+
+    return lerp(w,
+                lerp(v,
+                     lerp(u, grad(perm(aa), x, y, z), grad(perm(ba), x - 1.0, y, z)),
+                     lerp(u, grad(perm(ab), x, y - 1.0, z), grad(perm(bb), x - 1.0, y - 1.0, z))),
+                lerp(v,
+                     lerp(u, grad(perm(aa + 1), x, y, z - 1.0), grad(perm(ba + 1), x - 1.0, y, z - 1.0)),
+                     lerp(u, grad(perm(ab + 1), x, y - 1.0, z - 1.0), grad(perm(bb + 1), x - 1.0, y - 1.0, z - 1.0))));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::perlin_noise;
+
+    #[test]
+    fn noise_is_zero_on_integer_lattice_points() {
+        for x in -3..3 {
+            for y in -3..3 {
+                for z in -3..3 {
+                    assert_eq!(0.0, perlin_noise(x as f64, y as f64, z as f64));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn noise_is_repeatable() {
+        assert_eq!(perlin_noise(1.3, -2.7, 0.45), perlin_noise(1.3, -2.7, 0.45));
+    }
+
+    #[test]
+    fn noise_stays_within_range_and_varies() {
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+
+        for i in 0..1000 {
+            let t = i as f64 * 0.137;
+            let n = perlin_noise(t, t * 0.7 - 3.0, t * 1.3 + 5.0);
+            min = min.min(n);
+            max = max.max(n);
+        }
+
+        assert!(min >= -1.0 && max <= 1.0);
+        assert!(max - min > 0.5);
+    }
+
+    #[test]
+    fn noise_is_continuous() {
+        let n1 = perlin_noise(0.5, 0.5, 0.5);
+        let n2 = perlin_noise(0.5001, 0.5, 0.5);
+
+        assert!((n1 - n2).abs() < 0.001);
+    }
+}
@@ -0,0 +1,523 @@
+use std::fmt;
+use crate::tuple::Tuple;
+use crate::group::Group;
+use crate::material::Material;
+use crate::shape::Shape;
+use crate::triangle::Triangle;
+use crate::smooth_triangle::SmoothTriangle;
+
+// a malformed line in an OBJ file, line numbers start at 1
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct ObjError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "line {}: {}", self.line, self.message);
+    }
+}
+
+// one triangle of a face, as 0-based indices into the file's vertex, texture and normal lists
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub struct ObjFace {
+    pub vertices: [usize; 3],
+    pub texture_coords: Option<[usize; 3]>,
+    pub normals: Option<[usize; 3]>,
+}
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct ObjGroup {
+    pub name: String,
+    pub faces: Vec<ObjFace>,
+}
+
+#[derive(Debug)]
+pub struct ObjFile {
+    pub vertices: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    pub texture_coords: Vec<(f64, f64)>,
+    // faces that appear before any named group
+    pub default_group: Vec<ObjFace>,
+    pub groups: Vec<ObjGroup>,
+    // statements this parser does not understand, with their line numbers
+    pub ignored: Vec<(usize, String)>,
+}
+
+// a corner of a face as written in the file: v, v/vt, v//vn or v/vt/vn
+struct FaceVertex {
+    vertex: usize,
+    texture_coord: Option<usize>,
+    normal: Option<usize>,
+}
+
+impl ObjFile {
+    pub fn parse(input: &str) -> Result<ObjFile, ObjError> {
+        let mut obj = ObjFile {
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            texture_coords: Vec::new(),
+            default_group: Vec::new(),
+            groups: Vec::new(),
+            ignored: Vec::new(),
+        };
+        // index into groups of the group faces go to, None for the default group
+        let mut current_group: Option<usize> = None;
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = text.split_whitespace();
+            let keyword = parts.next().unwrap();
+            let args: Vec<&str> = parts.collect();
+
+            match keyword {
+                "v" => {
+                    let values = parse_numbers(&args, 3, 4, line_number)?;
+                    obj.vertices.push(Tuple::point(values[0], values[1], values[2]));
+                }
+                "vn" => {
+                    let values = parse_numbers(&args, 3, 3, line_number)?;
+                    obj.normals.push(Tuple::vector(values[0], values[1], values[2]));
+                }
+                "vt" => {
+                    let values = parse_numbers(&args, 1, 3, line_number)?;
+                    obj.texture_coords.push((values[0], *values.get(1).unwrap_or(&0.0)));
+                }
+                "f" => {
+                    let faces = obj.parse_face(&args, line_number)?;
+                    match current_group {
+                        Some(group) => obj.groups[group].faces.extend(faces),
+                        None => obj.default_group.extend(faces),
+                    }
+                }
+                "g" => {
+                    let name = args.join(" ");
+                    current_group = if name.is_empty() {
+                        None
+                    } else {
+                        // a group can be reopened later in the file
+                        match obj.groups.iter().position(|group| group.name == name) {
+                            Some(existing) => Some(existing),
+                            None => {
+                                obj.groups.push(ObjGroup { name, faces: Vec::new() });
+                                Some(obj.groups.len() - 1)
+                            }
+                        }
+                    };
+                }
+                _ => obj.ignored.push((line_number, text.to_string())),
+            }
+        }
+
+        return Ok(obj);
+    }
+
+    pub fn from_file(file_name: &str) -> Result<ObjFile, String> {
+        let contents = std::fs::read_to_string(file_name)
+            .map_err(|why| format!("couldn't read {}: {}", file_name, why))?;
+
+        return ObjFile::parse(&contents).map_err(|why| format!("couldn't load {}: {}", file_name, why));
+    }
+
+    #[allow(dead_code)]
+    pub fn group(&self, name: &str) -> Option<&ObjGroup> {
+        return self.groups.iter().find(|group| group.name == name);
+    }
+
+    // the whole file as one group, each named group becoming a child group of it
+    // OBJ files carry no materials, every triangle gets the given one
+    pub fn to_group(&self, material: &Material) -> Group {
+        let mut result = Group::new();
+        self.add_faces(&mut result, &self.default_group, material);
+
+        for obj_group in self.groups.iter() {
+            let mut group = Group::new();
+            self.add_faces(&mut group, &obj_group.faces, material);
+            result.add_child(Box::new(group));
+        }
+
+        return result;
+    }
+
+    // faces with normals on every corner become smooth triangles, the rest flat ones
+    fn add_faces(&self, group: &mut Group, faces: &[ObjFace], material: &Material) {
+        for face in faces.iter() {
+            let [p1, p2, p3] = face.vertices;
+
+            let mut triangle: Box<dyn Shape> = match face.normals {
+                Some([n1, n2, n3]) => Box::new(SmoothTriangle::new(
+                    self.vertices[p1], self.vertices[p2], self.vertices[p3],
+                    self.normals[n1], self.normals[n2], self.normals[n3],
+                )),
+                None => Box::new(Triangle::new(
+                    self.vertices[p1], self.vertices[p2], self.vertices[p3],
+                )),
+            };
+            triangle.set_material(material.clone());
+            group.add_child(triangle);
+        }
+    }
+
+    // splits a polygon into a fan of triangles sharing its first vertex
+    fn parse_face(&self, args: &[&str], line_number: usize) -> Result<Vec<ObjFace>, ObjError> {
+        if args.len() < 3 {
+            return Err(ObjError {
+                line: line_number,
+                message: format!("a face needs at least 3 vertices, found {}", args.len()),
+            });
+        }
+
+        let corners = args.iter()
+            .map(|arg| self.parse_face_vertex(arg, line_number))
+            .collect::<Result<Vec<FaceVertex>, ObjError>>()?;
+
+        let has_texture_coords = corners.iter().all(|corner| corner.texture_coord.is_some());
+        let has_normals = corners.iter().all(|corner| corner.normal.is_some());
+
+        let mut faces = Vec::new();
+        for index in 1..corners.len() - 1 {
+            let triangle = [&corners[0], &corners[index], &corners[index + 1]];
+
+            faces.push(ObjFace {
+                vertices: [triangle[0].vertex, triangle[1].vertex, triangle[2].vertex],
+                texture_coords: if has_texture_coords {
+                    Some([triangle[0].texture_coord.unwrap(), triangle[1].texture_coord.unwrap(),
+                        triangle[2].texture_coord.unwrap()])
+                } else {
+                    None
+                },
+                normals: if has_normals {
+                    Some([triangle[0].normal.unwrap(), triangle[1].normal.unwrap(), triangle[2].normal.unwrap()])
+                } else {
+                    None
+                },
+            });
+        }
+
+        return Ok(faces);
+    }
+
+    fn parse_face_vertex(&self, arg: &str, line_number: usize) -> Result<FaceVertex, ObjError> {
+        let parts: Vec<&str> = arg.split('/').collect();
+        if parts.len() > 3 {
+            return Err(ObjError { line: line_number, message: format!("invalid face vertex '{}'", arg) });
+        }
+
+        let vertex = resolve_index(parts[0], self.vertices.len(), "vertex", line_number)?;
+        let texture_coord = match parts.get(1) {
+            Some(part) if !part.is_empty() =>
+                Some(resolve_index(part, self.texture_coords.len(), "texture coordinate", line_number)?),
+            _ => None,
+        };
+        let normal = match parts.get(2) {
+            Some(part) if !part.is_empty() =>
+                Some(resolve_index(part, self.normals.len(), "normal", line_number)?),
+            _ => None,
+        };
+
+        return Ok(FaceVertex { vertex, texture_coord, normal });
+    }
+}
+
+fn parse_numbers(args: &[&str], min: usize, max: usize, line_number: usize) -> Result<Vec<f64>, ObjError> {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max { min.to_string() } else { format!("{} to {}", min, max) };
+        return Err(ObjError {
+            line: line_number,
+            message: format!("expected {} numbers, found {}", expected, args.len()),
+        });
+    }
+
+    return args.iter()
+        .map(|arg| arg.parse::<f64>()
+            .map_err(|_| ObjError { line: line_number, message: format!("invalid number '{}'", arg) }))
+        .collect();
+}
+
+// OBJ indices start at 1, negative ones count back from the last element defined so far
+fn resolve_index(text: &str, count: usize, kind: &str, line_number: usize) -> Result<usize, ObjError> {
+    let index = text.parse::<i64>()
+        .map_err(|_| ObjError { line: line_number, message: format!("invalid {} index '{}'", kind, text) })?;
+
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(ObjError {
+            line: line_number,
+            message: format!("{} index {} is out of range, {} defined so far", kind, index, count),
+        });
+    }
+
+    return Ok(resolved as usize);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::obj_file::{ObjFile, ObjFace, ObjError};
+    use crate::tuple::Tuple;
+    use crate::ray::Ray;
+    use crate::material::Material;
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright\n\
+            who traveled much faster than light.\n\
+            She set out one day\n\
+            in a relative way,\n\
+            and came back the previous night.";
+
+        let obj = ObjFile::parse(gibberish).unwrap();
+
+        assert_eq!(5, obj.ignored.len());
+        assert_eq!((3, "She set out one day".to_string()), obj.ignored[2]);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_not_reported() {
+        let obj = ObjFile::parse("# a comment\n\n   \nv 1 2 3\n").unwrap();
+
+        assert_eq!(0, obj.ignored.len());
+        assert_eq!(1, obj.vertices.len());
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = "v -1 1 0\n\
+            v -1.0000 0.5000 0.0000\n\
+            v 1 0 0\n\
+            v 1 1 0";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        assert_eq!(Tuple::point(-1.0, 1.0, 0.0), obj.vertices[0]);
+        assert_eq!(Tuple::point(-1.0, 0.5, 0.0), obj.vertices[1]);
+        assert_eq!(Tuple::point(1.0, 0.0, 0.0), obj.vertices[2]);
+        assert_eq!(Tuple::point(1.0, 1.0, 0.0), obj.vertices[3]);
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            \n\
+            f 1 2 3\n\
+            f 1 3 4";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        assert_eq!(vec!(
+            ObjFace { vertices: [0, 1, 2], texture_coords: None, normals: None },
+            ObjFace { vertices: [0, 2, 3], texture_coords: None, normals: None },
+        ), obj.default_group);
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            v 0 2 0\n\
+            \n\
+            f 1 2 3 4 5";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        let actual: Vec<[usize; 3]> = obj.default_group.iter().map(|face| face.vertices).collect();
+        assert_eq!(vec!([0, 1, 2], [0, 2, 3], [0, 3, 4]), actual);
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            \n\
+            g FirstGroup\n\
+            f 1 2 3\n\
+            g SecondGroup\n\
+            f 1 3 4";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        assert_eq!(0, obj.default_group.len());
+        assert_eq!(vec!([0, 1, 2]), obj.group("FirstGroup").unwrap().faces.iter().map(|f| f.vertices).collect::<Vec<_>>());
+        assert_eq!(vec!([0, 2, 3]), obj.group("SecondGroup").unwrap().faces.iter().map(|f| f.vertices).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reopening_a_group_adds_to_it() {
+        let file = "v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+            g A\nf 1 2 3\ng B\nf 1 2 3\ng A\nf 3 2 1";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        assert_eq!(2, obj.groups.len());
+        assert_eq!(2, obj.group("A").unwrap().faces.len());
+    }
+
+    #[test]
+    fn converting_an_obj_file_to_a_group() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            f 1 2 4\n\
+            g FirstGroup\n\
+            f 1 2 3\n\
+            g SecondGroup\n\
+            f 1 3 4";
+        let obj = ObjFile::parse(file).unwrap();
+
+        let g = obj.to_group(&Material::default());
+
+        // the default group's triangle, then one child group per named group
+        assert_eq!(3, g.children().len());
+        let r = Ray {
+            origin: Tuple::point(0.5, 0.1, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let xs = r.intersect(&g);
+        assert_eq!(1, xs.len());
+        assert!(g.children()[1].includes(xs[0].object));
+    }
+
+    #[test]
+    fn vertex_normal_records() {
+        let file = "vn 0 0 1\n\
+            vn 0.707 0 -0.707\n\
+            vn 1 2 3";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        assert_eq!(Tuple::vector(0.0, 0.0, 1.0), obj.normals[0]);
+        assert_eq!(Tuple::vector(0.707, 0.0, -0.707), obj.normals[1]);
+        assert_eq!(Tuple::vector(1.0, 2.0, 3.0), obj.normals[2]);
+    }
+
+    #[test]
+    fn texture_coordinate_records() {
+        let obj = ObjFile::parse("vt 0.25 0.75\nvt 0.5\nvt 0.1 0.2 0.3").unwrap();
+
+        assert_eq!(vec!((0.25, 0.75), (0.5, 0.0), (0.1, 0.2)), obj.texture_coords);
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let file = "v 0 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            \n\
+            vn -1 0 0\n\
+            vn 1 0 0\n\
+            vn 0 1 0\n\
+            \n\
+            f 1//3 2//1 3//2\n\
+            f 1//3 2//1 3//2";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        let expected = ObjFace { vertices: [0, 1, 2], texture_coords: None, normals: Some([2, 0, 1]) };
+        assert_eq!(vec!(expected, expected), obj.default_group);
+    }
+
+    #[test]
+    fn faces_with_texture_coordinates_and_normals() {
+        let file = "v 0 1 0\nv -1 0 0\nv 1 0 0\n\
+            vt 0.5 1\nvt 0 0\nvt 1 0\n\
+            vn 0 0 -1\n\
+            f 1/1/1 2/2/1 3/3/1\n\
+            f 1/1 2/2 3/3";
+
+        let obj = ObjFile::parse(file).unwrap();
+
+        assert_eq!(ObjFace { vertices: [0, 1, 2], texture_coords: Some([0, 1, 2]), normals: Some([0, 0, 0]) },
+                   obj.default_group[0]);
+        assert_eq!(ObjFace { vertices: [0, 1, 2], texture_coords: Some([0, 1, 2]), normals: None },
+                   obj.default_group[1]);
+    }
+
+    #[test]
+    fn negative_indices_count_back_from_the_last_vertex() {
+        let obj = ObjFile::parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1").unwrap();
+
+        assert_eq!([0, 1, 2], obj.default_group[0].vertices);
+    }
+
+    #[test]
+    fn faces_with_normals_become_smooth_triangles() {
+        let file = "v 0 1 0\nv -1 0 0\nv 1 0 0\n\
+            vn 0 0.6 -0.8\n\
+            f 1//1 2//1 3//1\n\
+            f 1 2 3";
+        let obj = ObjFile::parse(file).unwrap();
+        let g = obj.to_group(&Material::default());
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.5, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = r.intersect(&g);
+        let smooth = xs[0].object.normal_at_hit(r.position(xs[0].t), &xs[0]);
+        let flat = xs[1].object.normal_at_hit(r.position(xs[1].t), &xs[1]);
+
+        assert_eq!(Tuple::vector(0.0, 0.6, -0.8), smooth.round());
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), flat.round());
+    }
+
+    macro_rules! malformed_obj_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (file, expected) = $value;
+
+                let actual = ObjFile::parse(file);
+
+                assert_eq!(expected, actual.unwrap_err().to_string());
+            }
+        )*
+        }
+    }
+
+    malformed_obj_tests! {
+        a_vertex_with_a_bad_number: ("v 1 2 3\nv 1 x 3", "line 2: invalid number 'x'"),
+        a_vertex_with_too_few_numbers: ("v 1 2", "line 1: expected 3 to 4 numbers, found 2"),
+        a_normal_with_too_many_numbers: ("vn 1 2 3 4", "line 1: expected 3 numbers, found 4"),
+        a_face_with_too_few_vertices: ("v 0 0 0\nv 1 0 0\nf 1 2", "line 3: a face needs at least 3 vertices, found 2"),
+        a_face_referring_to_a_missing_vertex: ("v 0 0 0\nv 1 0 0\nf 1 2 3", "line 3: vertex index 3 is out of range, 2 defined so far"),
+        a_face_with_a_zero_index: ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2", "line 4: vertex index 0 is out of range, 3 defined so far"),
+        a_face_with_a_bad_index: ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 two 3", "line 4: invalid vertex index 'two'"),
+        a_face_referring_to_a_missing_normal: ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1//1 2//1 3//1", "line 4: normal index 1 is out of range, 0 defined so far"),
+    }
+
+    #[test]
+    fn obj_errors_carry_the_line_number() {
+        let error = ObjFile::parse("v 1 2 3\n\n# comment\nf 1 2 3").unwrap_err();
+
+        assert_eq!(ObjError { line: 4, message: "vertex index 2 is out of range, 1 defined so far".to_string() }, error);
+    }
+
+    #[test]
+    fn loading_an_obj_file_from_disk() {
+        let file_name = std::env::temp_dir().join("loading_an_obj_file_from_disk.obj");
+        std::fs::write(&file_name, "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+
+        let obj = ObjFile::from_file(file_name.to_str().unwrap()).unwrap();
+
+        assert_eq!(1, obj.default_group.len());
+        assert!(ObjFile::from_file("/no/such/model.obj").is_err());
+    }
+}
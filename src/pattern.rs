@@ -0,0 +1,526 @@
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::color::Color;
+use crate::matrix::Matrix4;
+use crate::tuple::Tuple;
+use crate::shape::Shape;
+use crate::noise::perlin_noise;
+
+pub trait Pattern: Debug {
+    fn transform(&self) -> Matrix4;
+    fn set_transform(&mut self, new_transform: Matrix4);
+
+    // the color at a point that was already converted to pattern space
+    fn pattern_at(&self, pattern_point: Tuple) -> Color;
+
+    fn pattern_at_shape(&self, object: &dyn Shape, world_point: Tuple) -> Color {
+        let object_point = object.world_to_object(world_point);
+
+        return self.pattern_at_parent(object_point);
+    }
+
+    // the color at a point in the space of whatever holds this pattern, a shape or an enclosing pattern
+    fn pattern_at_parent(&self, parent_point: Tuple) -> Color {
+        let pattern_point = self.transform().inverse() * parent_point;
+
+        return self.pattern_at(pattern_point);
+    }
+}
+
+#[macro_export]
+macro_rules! pattern_properties {
+    () => {
+        fn transform(&self) -> $crate::matrix::Matrix4 {
+            return self.transform;
+        }
+
+        fn set_transform(&mut self, new_transform: $crate::matrix::Matrix4) {
+            self.transform = new_transform;
+        }
+    }
+}
+
+// the same color everywhere, used as the leaves of composite patterns
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct SolidPattern {
+    pub color: Color,
+    pub(crate) transform: Matrix4,
+}
+
+impl SolidPattern {
+    pub fn new(color: Color) -> Self {
+        return SolidPattern { color, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for SolidPattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, _pattern_point: Tuple) -> Color {
+        return self.color;
+    }
+}
+
+fn solid(color: Color) -> Rc<dyn Pattern> {
+    return Rc::new(SolidPattern::new(color));
+}
+
+// alternates between two patterns along x
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct StripePattern {
+    pub a: Rc<dyn Pattern>,
+    pub b: Rc<dyn Pattern>,
+    pub(crate) transform: Matrix4,
+}
+
+impl StripePattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        return StripePattern::nested(solid(a), solid(b));
+    }
+
+    pub fn nested(a: Rc<dyn Pattern>, b: Rc<dyn Pattern>) -> Self {
+        return StripePattern { a, b, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for StripePattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, pattern_point: Tuple) -> Color {
+        if pattern_point.x.floor().rem_euclid(2.0) == 0.0 {
+            return self.a.pattern_at_parent(pattern_point);
+        }
+
+        return self.b.pattern_at_parent(pattern_point);
+    }
+}
+
+// blends linearly from one pattern to the other along x, repeating every unit
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct GradientPattern {
+    pub a: Rc<dyn Pattern>,
+    pub b: Rc<dyn Pattern>,
+    pub(crate) transform: Matrix4,
+}
+
+impl GradientPattern {
+    #[allow(dead_code)]
+    pub fn new(a: Color, b: Color) -> Self {
+        return GradientPattern::nested(solid(a), solid(b));
+    }
+
+    pub fn nested(a: Rc<dyn Pattern>, b: Rc<dyn Pattern>) -> Self {
+        return GradientPattern { a, b, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for GradientPattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, pattern_point: Tuple) -> Color {
+        let fraction = pattern_point.x - pattern_point.x.floor();
+        let a = self.a.pattern_at_parent(pattern_point);
+        let b = self.b.pattern_at_parent(pattern_point);
+
+        return a + (b - a) * fraction;
+    }
+}
+
+// concentric rings around the y axis
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct RingPattern {
+    pub a: Rc<dyn Pattern>,
+    pub b: Rc<dyn Pattern>,
+    pub(crate) transform: Matrix4,
+}
+
+impl RingPattern {
+    #[allow(dead_code)]
+    pub fn new(a: Color, b: Color) -> Self {
+        return RingPattern::nested(solid(a), solid(b));
+    }
+
+    pub fn nested(a: Rc<dyn Pattern>, b: Rc<dyn Pattern>) -> Self {
+        return RingPattern { a, b, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for RingPattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, pattern_point: Tuple) -> Color {
+        let distance = (pattern_point.x.powi(2) + pattern_point.z.powi(2)).sqrt();
+
+        if distance.floor().rem_euclid(2.0) == 0.0 {
+            return self.a.pattern_at_parent(pattern_point);
+        }
+
+        return self.b.pattern_at_parent(pattern_point);
+    }
+}
+
+// a 3D checkerboard of unit cubes
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct CheckersPattern {
+    pub a: Rc<dyn Pattern>,
+    pub b: Rc<dyn Pattern>,
+    pub(crate) transform: Matrix4,
+}
+
+impl CheckersPattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        return CheckersPattern::nested(solid(a), solid(b));
+    }
+
+    pub fn nested(a: Rc<dyn Pattern>, b: Rc<dyn Pattern>) -> Self {
+        return CheckersPattern { a, b, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for CheckersPattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, pattern_point: Tuple) -> Color {
+        let sum = pattern_point.x.floor() + pattern_point.y.floor() + pattern_point.z.floor();
+
+        if sum.rem_euclid(2.0) == 0.0 {
+            return self.a.pattern_at_parent(pattern_point);
+        }
+
+        return self.b.pattern_at_parent(pattern_point);
+    }
+}
+
+// mixes two patterns, weight 0 gives only a, 1 only b
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct BlendPattern {
+    pub a: Rc<dyn Pattern>,
+    pub b: Rc<dyn Pattern>,
+    pub weight: f64,
+    pub(crate) transform: Matrix4,
+}
+
+impl BlendPattern {
+    // an even mix of both patterns
+    pub fn new(a: Rc<dyn Pattern>, b: Rc<dyn Pattern>) -> Self {
+        return BlendPattern::with_weight(a, b, 0.5);
+    }
+
+    pub fn with_weight(a: Rc<dyn Pattern>, b: Rc<dyn Pattern>, weight: f64) -> Self {
+        return BlendPattern { a, b, weight, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for BlendPattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, pattern_point: Tuple) -> Color {
+        let a = self.a.pattern_at_parent(pattern_point);
+        let b = self.b.pattern_at_parent(pattern_point);
+
+        return a * (1.0 - self.weight) + b * self.weight;
+    }
+}
+
+// jitters the point with 3D noise before looking up the child pattern
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct PerturbedPattern {
+    pub pattern: Rc<dyn Pattern>,
+    // how far, at most, a point is moved along each axis
+    pub scale: f64,
+    pub(crate) transform: Matrix4,
+}
+
+impl PerturbedPattern {
+    pub fn new(pattern: Rc<dyn Pattern>, scale: f64) -> Self {
+        return PerturbedPattern { pattern, scale, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for PerturbedPattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, pattern_point: Tuple) -> Color {
+        let (x, y, z) = (pattern_point.x, pattern_point.y, pattern_point.z);
+
+        // offset the lookups so the three axes do not move in lockstep
+        let perturbed = Tuple::point(
+            x + perlin_noise(x, y, z) * self.scale,
+            y + perlin_noise(x, y, z + 1.0) * self.scale,
+            z + perlin_noise(x, y, z + 2.0) * self.scale,
+        );
+
+        return self.pattern.pattern_at_parent(perturbed);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::rc::Rc;
+    use crate::pattern::{Pattern, SolidPattern, StripePattern, GradientPattern, RingPattern, CheckersPattern,
+                         BlendPattern, PerturbedPattern};
+    use crate::color::Color;
+    use crate::matrix::Matrix4;
+    use crate::tuple::Tuple;
+    use crate::sphere::Sphere;
+    use crate::shape::Shape;
+
+    const WHITE: Color = Color { red: 1.0, green: 1.0, blue: 1.0 };
+    const BLACK: Color = Color { red: 0.0, green: 0.0, blue: 0.0 };
+
+    // returns the pattern space point as a color, to check which transforms were applied
+    #[derive(Debug)]
+    pub struct TestPattern {
+        pub(crate) transform: Matrix4,
+    }
+
+    impl TestPattern {
+        pub fn new() -> Self {
+            return TestPattern { transform: Matrix4::identity() };
+        }
+    }
+
+    impl Pattern for TestPattern {
+        pattern_properties!();
+
+        fn pattern_at(&self, pattern_point: Tuple) -> Color {
+            return Color::new(pattern_point.x, pattern_point.y, pattern_point.z);
+        }
+    }
+
+    #[test]
+    fn creating_a_stripe_pattern() {
+        let pattern = StripePattern::new(WHITE, BLACK);
+
+        assert_eq!(WHITE, pattern.a.pattern_at(Tuple::point(0.0, 0.0, 0.0)));
+        assert_eq!(BLACK, pattern.b.pattern_at(Tuple::point(0.0, 0.0, 0.0)));
+    }
+
+    macro_rules! pattern_at_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (pattern, point, expected) = $value;
+
+                let actual = pattern.pattern_at(point);
+
+                assert_eq!(expected, actual);
+            }
+        )*
+        }
+    }
+
+    pattern_at_tests! {
+        a_stripe_pattern_is_constant_in_y_0: (StripePattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 0.0), WHITE),
+        a_stripe_pattern_is_constant_in_y_1: (StripePattern::new(WHITE, BLACK), Tuple::point(0.0, 1.0, 0.0), WHITE),
+        a_stripe_pattern_is_constant_in_y_2: (StripePattern::new(WHITE, BLACK), Tuple::point(0.0, 2.0, 0.0), WHITE),
+        a_stripe_pattern_is_constant_in_z_1: (StripePattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 1.0), WHITE),
+        a_stripe_pattern_is_constant_in_z_2: (StripePattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 2.0), WHITE),
+        a_stripe_pattern_alternates_in_x_0_9: (StripePattern::new(WHITE, BLACK), Tuple::point(0.9, 0.0, 0.0), WHITE),
+        a_stripe_pattern_alternates_in_x_1: (StripePattern::new(WHITE, BLACK), Tuple::point(1.0, 0.0, 0.0), BLACK),
+        a_stripe_pattern_alternates_in_x_minus_0_1: (StripePattern::new(WHITE, BLACK), Tuple::point(-0.1, 0.0, 0.0), BLACK),
+        a_stripe_pattern_alternates_in_x_minus_1: (StripePattern::new(WHITE, BLACK), Tuple::point(-1.0, 0.0, 0.0), BLACK),
+        a_stripe_pattern_alternates_in_x_minus_1_1: (StripePattern::new(WHITE, BLACK), Tuple::point(-1.1, 0.0, 0.0), WHITE),
+        a_gradient_linearly_interpolates_at_0: (GradientPattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 0.0), WHITE),
+        a_gradient_linearly_interpolates_at_0_25: (GradientPattern::new(WHITE, BLACK), Tuple::point(0.25, 0.0, 0.0), Color::new(0.75, 0.75, 0.75)),
+        a_gradient_linearly_interpolates_at_0_5: (GradientPattern::new(WHITE, BLACK), Tuple::point(0.5, 0.0, 0.0), Color::new(0.5, 0.5, 0.5)),
+        a_gradient_linearly_interpolates_at_0_75: (GradientPattern::new(WHITE, BLACK), Tuple::point(0.75, 0.0, 0.0), Color::new(0.25, 0.25, 0.25)),
+        a_ring_should_extend_in_both_x_and_z_at_origin: (RingPattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 0.0), WHITE),
+        a_ring_should_extend_in_both_x_and_z_along_x: (RingPattern::new(WHITE, BLACK), Tuple::point(1.0, 0.0, 0.0), BLACK),
+        a_ring_should_extend_in_both_x_and_z_along_z: (RingPattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 1.0), BLACK),
+        a_ring_should_extend_in_both_x_and_z_diagonal: (RingPattern::new(WHITE, BLACK), Tuple::point(0.708, 0.0, 0.708), BLACK),
+        checkers_should_repeat_in_x_0: (CheckersPattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 0.0), WHITE),
+        checkers_should_repeat_in_x_0_99: (CheckersPattern::new(WHITE, BLACK), Tuple::point(0.99, 0.0, 0.0), WHITE),
+        checkers_should_repeat_in_x_1_01: (CheckersPattern::new(WHITE, BLACK), Tuple::point(1.01, 0.0, 0.0), BLACK),
+        checkers_should_repeat_in_y_0_99: (CheckersPattern::new(WHITE, BLACK), Tuple::point(0.0, 0.99, 0.0), WHITE),
+        checkers_should_repeat_in_y_1_01: (CheckersPattern::new(WHITE, BLACK), Tuple::point(0.0, 1.01, 0.0), BLACK),
+        checkers_should_repeat_in_z_0_99: (CheckersPattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 0.99), WHITE),
+        checkers_should_repeat_in_z_1_01: (CheckersPattern::new(WHITE, BLACK), Tuple::point(0.0, 0.0, 1.01), BLACK),
+    }
+
+    #[test]
+    fn the_default_pattern_transformation() {
+        let pattern = TestPattern::new();
+
+        assert_eq!(Matrix4::identity(), pattern.transform());
+    }
+
+    #[test]
+    fn assigning_a_transformation() {
+        let mut pattern = TestPattern::new();
+
+        pattern.set_transform(Matrix4::translation(1.0, 2.0, 3.0));
+
+        assert_eq!(Matrix4::translation(1.0, 2.0, 3.0), pattern.transform());
+    }
+
+    #[test]
+    fn a_pattern_with_an_object_transformation() {
+        let mut shape = Sphere::new();
+        shape.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let pattern = TestPattern::new();
+
+        let c = pattern.pattern_at_shape(&shape, Tuple::point(2.0, 3.0, 4.0));
+
+        assert_eq!(Color::new(1.0, 1.5, 2.0), c);
+    }
+
+    #[test]
+    fn a_pattern_with_a_pattern_transformation() {
+        let shape = Sphere::new();
+        let mut pattern = TestPattern::new();
+        pattern.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+
+        let c = pattern.pattern_at_shape(&shape, Tuple::point(2.0, 3.0, 4.0));
+
+        assert_eq!(Color::new(1.0, 1.5, 2.0), c);
+    }
+
+    #[test]
+    fn a_pattern_with_both_an_object_and_a_pattern_transformation() {
+        let mut shape = Sphere::new();
+        shape.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let mut pattern = TestPattern::new();
+        pattern.set_transform(Matrix4::translation(0.5, 1.0, 1.5));
+
+        let c = pattern.pattern_at_shape(&shape, Tuple::point(2.5, 3.0, 3.5));
+
+        assert_eq!(Color::new(0.75, 0.5, 0.25), c);
+    }
+
+    #[test]
+    fn stripes_with_an_object_transformation() {
+        let mut object = Sphere::new();
+        object.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let pattern = StripePattern::new(WHITE, BLACK);
+
+        let c = pattern.pattern_at_shape(&object, Tuple::point(1.5, 0.0, 0.0));
+
+        assert_eq!(WHITE, c);
+    }
+
+    #[test]
+    fn stripes_with_a_pattern_transformation() {
+        let object = Sphere::new();
+        let mut pattern = StripePattern::new(WHITE, BLACK);
+        pattern.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+
+        let c = pattern.pattern_at_shape(&object, Tuple::point(1.5, 0.0, 0.0));
+
+        assert_eq!(WHITE, c);
+    }
+
+    #[test]
+    fn stripes_with_both_an_object_and_a_pattern_transformation() {
+        let mut object = Sphere::new();
+        object.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let mut pattern = StripePattern::new(WHITE, BLACK);
+        pattern.set_transform(Matrix4::translation(0.5, 0.0, 0.0));
+
+        let c = pattern.pattern_at_shape(&object, Tuple::point(2.5, 0.0, 0.0));
+
+        assert_eq!(WHITE, c);
+    }
+
+    #[test]
+    fn a_solid_pattern_is_the_same_everywhere() {
+        let pattern = SolidPattern::new(WHITE);
+
+        assert_eq!(WHITE, pattern.pattern_at(Tuple::point(0.0, 0.0, 0.0)));
+        assert_eq!(WHITE, pattern.pattern_at(Tuple::point(-3.5, 7.2, 1.1)));
+    }
+
+    #[test]
+    fn a_nested_pattern_looks_up_its_children() {
+        let stripes = Rc::new(StripePattern::new(WHITE, BLACK));
+        let solid = Rc::new(SolidPattern::new(Color::new(0.5, 0.5, 0.5)));
+        let pattern = CheckersPattern::nested(stripes, solid);
+
+        // the first cube holds stripes, the next one along y is solid
+        assert_eq!(WHITE, pattern.pattern_at(Tuple::point(0.5, 0.5, 0.5)));
+        assert_eq!(BLACK, pattern.pattern_at(Tuple::point(-0.5, 0.5, -0.5)));
+        assert_eq!(Color::new(0.5, 0.5, 0.5), pattern.pattern_at(Tuple::point(0.5, 1.5, 0.5)));
+    }
+
+    #[test]
+    fn a_nested_pattern_applies_the_child_transformation() {
+        let mut child = TestPattern::new();
+        child.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let mut pattern = StripePattern::nested(Rc::new(child), Rc::new(SolidPattern::new(BLACK)));
+        pattern.set_transform(Matrix4::translation(0.5, 0.0, 0.0));
+        let shape = Sphere::new();
+
+        let c = pattern.pattern_at_shape(&shape, Tuple::point(1.0, 2.0, 3.0));
+
+        assert_eq!(Color::new(0.25, 1.0, 1.5), c);
+    }
+
+    #[test]
+    fn blending_two_patterns_averages_them_by_default() {
+        let pattern = BlendPattern::new(Rc::new(SolidPattern::new(WHITE)), Rc::new(SolidPattern::new(BLACK)));
+
+        assert_eq!(0.5, pattern.weight);
+        assert_eq!(Color::new(0.5, 0.5, 0.5), pattern.pattern_at(Tuple::point(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn blending_two_patterns_by_weight() {
+        let a = Rc::new(SolidPattern::new(Color::new(1.0, 0.0, 0.0)));
+        let b = Rc::new(SolidPattern::new(Color::new(0.0, 0.0, 1.0)));
+        let pattern = BlendPattern::with_weight(a, b, 0.25);
+
+        let c = pattern.pattern_at(Tuple::point(0.0, 0.0, 0.0));
+
+        assert_eq!(Color::new(0.75, 0.0, 0.25), c);
+    }
+
+    #[test]
+    fn blending_crossed_stripes() {
+        let horizontal = StripePattern::new(WHITE, BLACK);
+        let mut vertical = StripePattern::new(WHITE, BLACK);
+        vertical.set_transform(Matrix4::rotation_y(std::f64::consts::PI / 2.0));
+        let pattern = BlendPattern::new(Rc::new(horizontal), Rc::new(vertical));
+
+        assert_eq!(WHITE, pattern.pattern_at(Tuple::point(0.5, 0.0, -0.5)));
+        assert_eq!(Color::new(0.5, 0.5, 0.5), pattern.pattern_at(Tuple::point(1.5, 0.0, -0.5)));
+        assert_eq!(BLACK, pattern.pattern_at(Tuple::point(1.5, 0.0, -1.5)));
+    }
+
+    #[test]
+    fn a_perturbed_pattern_with_no_scale_matches_its_child() {
+        let pattern = PerturbedPattern::new(Rc::new(TestPattern::new()), 0.0);
+
+        let c = pattern.pattern_at(Tuple::point(0.3, 0.6, 0.9));
+
+        assert_eq!(Color::new(0.3, 0.6, 0.9), c);
+    }
+
+    #[test]
+    fn a_perturbed_pattern_moves_points_by_at_most_its_scale() {
+        let pattern = PerturbedPattern::new(Rc::new(TestPattern::new()), 0.2);
+        let point = Tuple::point(0.3, 0.6, 0.9);
+
+        let c = pattern.pattern_at(point);
+
+        assert_ne!(Color::new(point.x, point.y, point.z), c);
+        assert!((c.red - point.x).abs() <= 0.2);
+        assert!((c.green - point.y).abs() <= 0.2);
+        assert!((c.blue - point.z).abs() <= 0.2);
+    }
+
+    #[test]
+    fn a_perturbed_pattern_is_repeatable() {
+        let pattern = PerturbedPattern::new(Rc::new(StripePattern::new(WHITE, BLACK)), 0.5);
+        let point = Tuple::point(0.95, 0.4, 0.2);
+
+        assert_eq!(pattern.pattern_at(point), pattern.pattern_at(point));
+    }
+}
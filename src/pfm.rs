@@ -0,0 +1,158 @@
+use std::io::{self, Write};
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::ppm::PpmReader;
+
+impl Canvas {
+    // Portable FloatMap: 32-bit little-endian floats, stored bottom row first
+    pub fn write_pfm<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        // a negative scale marks the samples as little-endian
+        write!(writer, "PF\n{} {}\n-1.0\n", self.width(), self.height())?;
+
+        let mut row = Vec::with_capacity(self.width() * 3 * 4);
+        for y in (0..self.height()).rev() {
+            row.clear();
+            for x in 0..self.width() {
+                let color = self.pixel_at(x, y);
+                for component in [color.red, color.green, color.blue].iter() {
+                    row.extend_from_slice(&(*component as f32).to_le_bytes());
+                }
+            }
+            writer.write_all(&row)?;
+        }
+
+        return Ok(());
+    }
+
+    // reads color (PF) and grayscale (Pf) float maps of either byte order
+    pub fn from_pfm(pfm: &[u8]) -> Result<Canvas, String> {
+        let mut reader = PpmReader::new(pfm);
+
+        let channels = match reader.next_token().as_deref() {
+            Some("PF") => 3,
+            Some("Pf") => 1,
+            Some(magic) => return Err(format!("unsupported magic number '{}', expected PF or Pf", magic)),
+            None => return Err("empty file, expected the PF or Pf magic number".to_string()),
+        };
+
+        let width = reader.next_number("width")?;
+        let height = reader.next_number("height")?;
+
+        // the scale's sign gives the byte order, its magnitude only describes units and is ignored like most readers do
+        let scale = reader.next_token().ok_or("missing scale")?;
+        let little_endian = match scale.parse::<f64>() {
+            Ok(value) if value < 0.0 => true,
+            Ok(value) if value > 0.0 => false,
+            _ => return Err(format!("invalid scale '{}'", scale)),
+        };
+        reader.skip_raster_separator("scale")?;
+
+        let expected = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(channels * 4))
+            .ok_or(format!("image size {}x{} is too large", width, height))?;
+        let raster = reader.remaining();
+        if raster.len() < expected {
+            return Err(format!("truncated pixel data, expected {} bytes but found {}", expected, raster.len()));
+        }
+
+        let samples: Vec<f64> = raster[..expected].chunks(4)
+            .map(|bytes| {
+                let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+                let value = if little_endian { f32::from_le_bytes(bytes) } else { f32::from_be_bytes(bytes) };
+                return value as f64;
+            })
+            .collect();
+
+        let mut canvas = Canvas::new(width, height);
+        for (pixel, values) in samples.chunks(channels).enumerate() {
+            let color = if channels == 3 {
+                Color::new(values[0], values[1], values[2])
+            } else {
+                Color::new(values[0], values[0], values[0])
+            };
+            canvas.write_pixel(pixel % width, height - 1 - pixel / width, color);
+        }
+
+        return Ok(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::Canvas;
+    use crate::canvas::tests::test_canvas;
+    use crate::color::Color;
+
+    #[test]
+    fn writing_a_pfm() {
+        let mut out = Vec::new();
+
+        test_canvas().write_pfm(&mut out).unwrap();
+
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(header, &out[..header.len()]);
+        assert_eq!(header.len() + 2 * 2 * 3 * 4, out.len());
+        // the bottom row comes first
+        assert_eq!((-0.5f32).to_le_bytes(), out[header.len()..header.len() + 4]);
+    }
+
+    #[test]
+    fn pfm_round_trip_keeps_values_outside_zero_to_one() {
+        let mut out = Vec::new();
+        test_canvas().write_pfm(&mut out).unwrap();
+
+        let back = Canvas::from_pfm(&out).unwrap();
+
+        assert_eq!((2, 2), (back.width(), back.height()));
+        assert_eq!(Color::new(0.0, 1.5, 0.0), back.pixel_at(1, 0));
+        assert_eq!(Color::new(-0.5, 0.0, 1.0), back.pixel_at(0, 1));
+    }
+
+    #[test]
+    fn pfm_round_trip_rounds_to_single_precision() {
+        let mut c = Canvas::new(1, 1);
+        c.write_pixel(0, 0, Color::new(0.1, 1.0 / 3.0, 1e10));
+        let mut out = Vec::new();
+        c.write_pfm(&mut out).unwrap();
+
+        let back = Canvas::from_pfm(&out).unwrap().pixel_at(0, 0);
+
+        assert_eq!(0.1f32 as f64, back.red);
+        assert_eq!((1.0f32 / 3.0) as f64, back.green);
+        assert_eq!(1e10f32 as f64, back.blue);
+    }
+
+    #[test]
+    fn reading_a_big_endian_grayscale_pfm() {
+        let mut pfm = b"Pf\n2 1\n1.0\n".to_vec();
+        pfm.extend_from_slice(&0.25f32.to_be_bytes());
+        pfm.extend_from_slice(&2.0f32.to_be_bytes());
+
+        let c = Canvas::from_pfm(&pfm).unwrap();
+
+        assert_eq!(Color::new(0.25, 0.25, 0.25), c.pixel_at(0, 0));
+        assert_eq!(Color::new(2.0, 2.0, 2.0), c.pixel_at(1, 0));
+    }
+
+    macro_rules! malformed_pfm_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (pfm, expected): (&[u8], &str) = $value;
+
+                assert_eq!(Err(expected.to_string()), Canvas::from_pfm(pfm));
+            }
+        )*
+        }
+    }
+
+    malformed_pfm_tests! {
+        pfm_with_the_wrong_magic_number: (b"P6\n1 1\n255\n", "unsupported magic number 'P6', expected PF or Pf"),
+        pfm_that_is_empty: (b"", "empty file, expected the PF or Pf magic number"),
+        pfm_without_a_scale: (b"PF\n1 1\n", "missing scale"),
+        pfm_with_a_zero_scale: (b"PF\n1 1\n0.0\n", "invalid scale '0.0'"),
+        pfm_with_an_invalid_scale: (b"PF\n1 1\nbig\n", "invalid scale 'big'"),
+        pfm_without_a_raster_separator: (b"PF\n1 1\n-1.0", "expected a single whitespace character after the scale"),
+        pfm_with_truncated_pixel_data: (b"PF\n1 1\n-1.0\n\x00\x00\x80\x3f", "truncated pixel data, expected 12 bytes but found 4"),
+    }
+}
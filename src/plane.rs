@@ -0,0 +1,145 @@
+use crate::matrix::Matrix4;
+use crate::tuple::{Tuple, EPSILON};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+use crate::shape_properties;
+
+// an infinite plane spanning x and z, facing +y in object space
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Plane {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+}
+
+impl Plane {
+    pub fn new() -> Self {
+        return Plane {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+        };
+    }
+}
+
+impl Shape for Plane {
+    shape_properties!();
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        // parallel and coplanar rays never cross the plane
+        if local_ray.direction.y.abs() < EPSILON {
+            return Intersections::new();
+        }
+
+        let t = -local_ray.origin.y / local_ray.direction.y;
+
+        return Intersections {
+            values: vec!(Intersection::new(t, self))
+        };
+    }
+
+    fn local_normal_at(&self, _local_point: Tuple) -> Tuple {
+        return Tuple::vector(0.0, 1.0, 0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use crate::plane::Plane;
+    use crate::shape::Shape;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+    use crate::matrix::Matrix4;
+
+    #[test]
+    fn the_normal_of_a_plane_is_constant_everywhere() {
+        let p = Plane::new();
+
+        let n1 = p.local_normal_at(Tuple::point(0.0, 0.0, 0.0));
+        let n2 = p.local_normal_at(Tuple::point(10.0, 0.0, -10.0));
+        let n3 = p.local_normal_at(Tuple::point(-5.0, 0.0, 150.0));
+
+        assert_eq!(Tuple::vector(0.0, 1.0, 0.0), n1);
+        assert_eq!(Tuple::vector(0.0, 1.0, 0.0), n2);
+        assert_eq!(Tuple::vector(0.0, 1.0, 0.0), n3);
+    }
+
+    macro_rules! plane_intersection_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction, expected) = $value;
+
+                let p = Plane::new();
+                let r = Ray { origin, direction };
+
+                let xs = p.local_intersect(&r);
+
+                let actual: Vec<f64> = xs.values.iter().map(|i| i.t).collect();
+                assert_eq!(expected, actual);
+                for i in xs.values {
+                    assert_eq!(p.id, i.object.id());
+                }
+            }
+        )*
+        }
+    }
+
+    plane_intersection_tests! {
+        intersect_with_a_ray_parallel_to_the_plane: (
+            Tuple::point(0.0, 10.0, 0.0), Tuple::vector(0.0, 0.0, 1.0), Vec::<f64>::new()),
+        intersect_with_a_coplanar_ray: (
+            Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0), Vec::<f64>::new()),
+        a_ray_intersecting_a_plane_from_above: (
+            Tuple::point(0.0, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0), vec!(1.0)),
+        a_ray_intersecting_a_plane_from_below: (
+            Tuple::point(0.0, -1.0, 0.0), Tuple::vector(0.0, 1.0, 0.0), vec!(1.0)),
+    }
+
+    #[test]
+    fn intersecting_a_translated_plane() {
+        let mut p = Plane::new();
+        p.set_transform(Matrix4::translation(0.0, -2.0, 0.0));
+        let r = Ray {
+            origin: Tuple::point(0.0, 3.0, 0.0),
+            direction: Tuple::vector(0.0, -1.0, 0.0),
+        };
+
+        let xs = r.intersect(&p);
+
+        assert_eq!(1, xs.len());
+        assert_eq!(5.0, xs[0].t);
+    }
+
+    #[test]
+    fn the_normal_of_a_rotated_plane() {
+        let mut p = Plane::new();
+        p.set_transform(Matrix4::rotation_z(PI / 2.0));
+
+        let n = p.normal_at(Tuple::point(0.0, 5.0, 0.0));
+
+        assert_eq!(Tuple::vector(-1.0, 0.0, 0.0), n.round());
+    }
+
+    #[test]
+    fn a_ray_misses_a_plane_rotated_parallel_to_it() {
+        let mut p = Plane::new();
+        p.set_transform(Matrix4::rotation_x(PI / 2.0).translate(0.0, 0.0, 5.0));
+        let r = Ray {
+            origin: Tuple::point(0.0, 1.0, 0.0),
+            direction: Tuple::vector(0.0, 1.0, 0.0),
+        };
+
+        let xs = r.intersect(&p);
+
+        assert_eq!(0, xs.len());
+    }
+}
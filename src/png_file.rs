@@ -0,0 +1,167 @@
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use crate::canvas::Canvas;
+use crate::ppm::quantize;
+
+// bits per channel of the encoded image
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum PngDepth {
+    Eight,
+    Sixteen,
+}
+
+impl PngDepth {
+    pub fn maxval(&self) -> u16 {
+        return match self {
+            PngDepth::Eight => 255,
+            PngDepth::Sixteen => 65535,
+        };
+    }
+}
+
+impl Canvas {
+    // RGB, or RGBA with the canvas' alpha plane when alpha is set
+    pub fn write_png<W: Write>(&self, writer: &mut W, depth: PngDepth, alpha: bool) -> io::Result<()> {
+        let too_large = |_| io::Error::new(io::ErrorKind::InvalidInput, "canvas is too large for a PNG image");
+        let width = u32::try_from(self.width()).map_err(too_large)?;
+        let height = u32::try_from(self.height()).map_err(too_large)?;
+
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(if alpha { png::ColorType::Rgba } else { png::ColorType::Rgb });
+        encoder.set_depth(match depth {
+            PngDepth::Eight => png::BitDepth::Eight,
+            PngDepth::Sixteen => png::BitDepth::Sixteen,
+        });
+        encoder.set_compression(png::Compression::Best);
+        encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+
+        let mut png_writer = encoder.write_header()?;
+        png_writer.write_image_data(&self.png_samples(depth, alpha))?;
+        png_writer.finish()?;
+
+        return Ok(());
+    }
+
+    // rows top to bottom, 16-bit samples are big-endian as PNG requires
+    fn png_samples(&self, depth: PngDepth, alpha: bool) -> Vec<u8> {
+        let maxval = depth.maxval();
+        let channels = if alpha { 4 } else { 3 };
+        let bytes_per_sample = if depth == PngDepth::Eight { 1 } else { 2 };
+        let mut data = Vec::with_capacity(self.width() * self.height() * channels * bytes_per_sample);
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let color = self.pixel_at(x, y);
+                let mut samples = vec!(
+                    quantize(color.red, maxval),
+                    quantize(color.green, maxval),
+                    quantize(color.blue, maxval),
+                );
+                if alpha {
+                    samples.push(quantize(self.alpha_at(x, y), maxval));
+                }
+
+                for sample in samples {
+                    if depth == PngDepth::Eight {
+                        data.push(sample as u8);
+                    } else {
+                        data.extend_from_slice(&sample.to_be_bytes());
+                    }
+                }
+            }
+        }
+
+        return data;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::png_file::PngDepth;
+    use crate::canvas::Canvas;
+    use crate::canvas::tests::test_canvas;
+
+    fn decode(bytes: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+        data.truncate(info.buffer_size());
+
+        return (info, data);
+    }
+
+    #[test]
+    fn writing_an_8_bit_rgb_png() {
+        let mut out = Vec::new();
+
+        test_canvas().write_png(&mut out, PngDepth::Eight, false).unwrap();
+
+        let (info, data) = decode(&out);
+        assert_eq!((2, 2), (info.width, info.height));
+        assert_eq!(png::ColorType::Rgb, info.color_type);
+        assert_eq!(png::BitDepth::Eight, info.bit_depth);
+        assert_eq!(vec!(255, 0, 128, 0, 255, 0), data[..6].to_vec());
+        assert_eq!(vec!(0, 0, 255, 51, 102, 153), data[6..].to_vec());
+    }
+
+    #[test]
+    fn writing_a_16_bit_rgb_png() {
+        let mut out = Vec::new();
+
+        test_canvas().write_png(&mut out, PngDepth::Sixteen, false).unwrap();
+
+        let (info, data) = decode(&out);
+        assert_eq!(png::BitDepth::Sixteen, info.bit_depth);
+        assert_eq!(2 * 2 * 3 * 2, data.len());
+        assert_eq!(vec!(0xff, 0xff, 0x00, 0x00, 0x80, 0x00), data[..6].to_vec());
+        assert_eq!(vec!(0x33, 0x33, 0x66, 0x66, 0x99, 0x99), data[18..24].to_vec());
+    }
+
+    #[test]
+    fn writing_an_8_bit_png_with_alpha() {
+        let mut out = Vec::new();
+
+        test_canvas().write_png(&mut out, PngDepth::Eight, true).unwrap();
+
+        let (info, data) = decode(&out);
+        assert_eq!(png::ColorType::Rgba, info.color_type);
+        assert_eq!(vec!(255, 0, 128, 255, 0, 255, 0, 255), data[..8].to_vec());
+    }
+
+    #[test]
+    fn writing_a_16_bit_png_with_alpha() {
+        let mut out = Vec::new();
+
+        test_canvas().write_png(&mut out, PngDepth::Sixteen, true).unwrap();
+
+        let (info, data) = decode(&out);
+        assert_eq!(png::ColorType::Rgba, info.color_type);
+        assert_eq!(png::BitDepth::Sixteen, info.bit_depth);
+        assert_eq!(vec!(0xff, 0xff), data[6..8].to_vec());
+    }
+
+    #[test]
+    fn writing_a_png_with_partial_coverage() {
+        let mut c = test_canvas();
+        c.write_alpha(1, 0, 0.5);
+        c.write_alpha(0, 1, 0.0);
+        let mut out = Vec::new();
+
+        c.write_png(&mut out, PngDepth::Eight, true).unwrap();
+
+        let (_, data) = decode(&out);
+        assert_eq!(vec!(255, 128, 0), vec!(data[3], data[7], data[11]));
+    }
+
+    #[test]
+    fn png_output_is_compressed() {
+        let c = Canvas::new(100, 100);
+        let mut out = Vec::new();
+
+        c.write_png(&mut out, PngDepth::Eight, false).unwrap();
+
+        assert!(out.len() < 100 * 100 * 3 / 10);
+    }
+}
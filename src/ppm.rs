@@ -0,0 +1,573 @@
+use std::io::{self, Write};
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+// plain PPMs are readable text, binary ones are much smaller
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum PpmFormat {
+    Plain,
+    Binary,
+}
+
+impl PpmFormat {
+    fn magic_number(&self) -> &'static str {
+        return match self {
+            PpmFormat::Plain => "P3",
+            PpmFormat::Binary => "P6",
+        };
+    }
+}
+
+// plain PPM lines must not be longer than this
+const MAX_LINE_LENGTH: usize = 70;
+
+// clamps a color component to 0..1 and rounds it to the nearest of 0..=maxval,
+// NaN stays NaN through the clamp and the cast turns it into 0
+pub fn quantize(value: f64, maxval: u16) -> u16 {
+    let clamped = value.clamp(0.0, 1.0);
+
+    return (clamped * maxval as f64).round() as u16;
+}
+
+impl Canvas {
+    // maxval 255 gives 8-bit samples, anything above uses 16 bits per sample (up to 65535)
+    pub fn write_ppm<W: Write>(&self, writer: &mut W, format: PpmFormat, maxval: u16) -> io::Result<()> {
+        if maxval == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "maxval must be at least 1"));
+        }
+
+        write!(writer, "{}\n{} {}\n{}\n", format.magic_number(), self.width(), self.height(), maxval)?;
+
+        return match format {
+            PpmFormat::Plain => self.write_plain_pixels(writer, maxval),
+            PpmFormat::Binary => self.write_binary_pixels(writer, maxval),
+        };
+    }
+
+    // one line per row, wrapped so no line is longer than 70 characters
+    fn write_plain_pixels<W: Write>(&self, writer: &mut W, maxval: u16) -> io::Result<()> {
+        let mut line = String::with_capacity(MAX_LINE_LENGTH);
+
+        for y in 0..self.height() {
+            line.clear();
+            for x in 0..self.width() {
+                let color = self.pixel_at(x, y);
+                for component in [color.red, color.green, color.blue].iter() {
+                    let sample = quantize(*component, maxval).to_string();
+
+                    if !line.is_empty() && line.len() + 1 + sample.len() > MAX_LINE_LENGTH {
+                        writeln!(writer, "{}", line)?;
+                        line.clear();
+                    }
+                    if !line.is_empty() {
+                        line.push(' ');
+                    }
+                    line.push_str(&sample);
+                }
+            }
+            writeln!(writer, "{}", line)?;
+        }
+
+        return Ok(());
+    }
+
+    // samples are one byte each up to maxval 255, two big-endian bytes above that
+    fn write_binary_pixels<W: Write>(&self, writer: &mut W, maxval: u16) -> io::Result<()> {
+        let bytes_per_sample = if maxval < 256 { 1 } else { 2 };
+        let mut row = Vec::with_capacity(self.width() * 3 * bytes_per_sample);
+
+        for y in 0..self.height() {
+            row.clear();
+            for x in 0..self.width() {
+                let color = self.pixel_at(x, y);
+                for component in [color.red, color.green, color.blue].iter() {
+                    let sample = quantize(*component, maxval);
+                    if bytes_per_sample == 1 {
+                        row.push(sample as u8);
+                    } else {
+                        row.extend_from_slice(&sample.to_be_bytes());
+                    }
+                }
+            }
+            writer.write_all(&row)?;
+        }
+
+        return Ok(());
+    }
+}
+
+// the whitespace characters the netpbm format allows between header fields and plain samples
+fn is_whitespace(byte: u8) -> bool {
+    return matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c);
+}
+
+// walks the header and plain pixel data token by token, skipping whitespace and # comments
+pub(crate) struct PpmReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PpmReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        return PpmReader { bytes, position: 0 };
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while self.position < self.bytes.len() {
+            let byte = self.bytes[self.position];
+            if is_whitespace(byte) {
+                self.position += 1;
+            } else if byte == b'#' {
+                while self.position < self.bytes.len() && !matches!(self.bytes[self.position], b'\n' | b'\r') {
+                    self.position += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub(crate) fn next_token(&mut self) -> Option<String> {
+        self.skip_whitespace_and_comments();
+
+        let start = self.position;
+        while self.position < self.bytes.len()
+            && !is_whitespace(self.bytes[self.position])
+            && self.bytes[self.position] != b'#' {
+            self.position += 1;
+        }
+
+        if start == self.position {
+            return None;
+        }
+        return Some(String::from_utf8_lossy(&self.bytes[start..self.position]).into_owned());
+    }
+
+    pub(crate) fn next_number(&mut self, name: &str) -> Result<usize, String> {
+        let token = self.next_token().ok_or(format!("missing {}", name))?;
+        return token.parse::<usize>().map_err(|_| format!("invalid {} '{}'", name, token));
+    }
+
+    pub(crate) fn remaining(&self) -> &'a [u8] {
+        return &self.bytes[self.position..];
+    }
+
+    // exactly one whitespace byte separates a binary header from its raster
+    pub(crate) fn skip_raster_separator(&mut self, after: &str) -> Result<(), String> {
+        return match self.remaining().first() {
+            Some(byte) if is_whitespace(*byte) => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(format!("expected a single whitespace character after the {}", after)),
+        };
+    }
+}
+
+const SAMPLE_NAMES: [&str; 3] = ["red", "green", "blue"];
+
+impl Canvas {
+    // reads a plain (P3) or binary (P6) PPM image, scaling the samples by the file's maxval into 0..1
+    pub fn from_ppm(ppm: &[u8]) -> Result<Canvas, String> {
+        let mut reader = PpmReader::new(ppm);
+
+        let format = match reader.next_token().as_deref() {
+            Some("P3") => PpmFormat::Plain,
+            Some("P6") => PpmFormat::Binary,
+            Some(magic) => return Err(format!("unsupported magic number '{}', expected P3 or P6", magic)),
+            None => return Err("empty file, expected the P3 or P6 magic number".to_string()),
+        };
+
+        let width = reader.next_number("width")?;
+        let height = reader.next_number("height")?;
+        let maxval = reader.next_number("maxval")?;
+        if maxval == 0 || maxval > u16::MAX as usize {
+            return Err(format!("invalid maxval '{}', expected 1 to 65535", maxval));
+        }
+
+        let samples = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(3))
+            .ok_or(format!("image size {}x{} is too large", width, height))?;
+
+        let values = match format {
+            PpmFormat::Plain => read_plain_samples(&mut reader, width, samples, maxval)?,
+            PpmFormat::Binary => read_binary_samples(&mut reader, width, samples, maxval)?,
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        for (pixel, rgb) in values.chunks(3).enumerate() {
+            let color = Color::new(
+                rgb[0] as f64 / maxval as f64,
+                rgb[1] as f64 / maxval as f64,
+                rgb[2] as f64 / maxval as f64,
+            );
+            canvas.write_pixel(pixel % width, pixel / width, color);
+        }
+
+        return Ok(canvas);
+    }
+}
+
+// names a sample by its position in the raster, for error messages
+fn describe_sample(index: usize, width: usize) -> String {
+    let pixel = index / 3;
+    return format!("{} sample of pixel ({}, {})", SAMPLE_NAMES[index % 3], pixel % width, pixel / width);
+}
+
+fn check_sample(value: usize, index: usize, width: usize, maxval: usize) -> Result<(), String> {
+    if value > maxval {
+        return Err(format!("{} is {}, larger than maxval {}", describe_sample(index, width), value, maxval));
+    }
+    return Ok(());
+}
+
+fn read_plain_samples(reader: &mut PpmReader, width: usize, samples: usize, maxval: usize) -> Result<Vec<usize>, String> {
+    // every sample takes at least one byte, so a short file is rejected before allocating the raster
+    if reader.remaining().len() < samples {
+        return Err(format!("truncated pixel data, {} samples need more than the {} bytes left", samples, reader.remaining().len()));
+    }
+
+    let mut values = Vec::with_capacity(samples);
+    for index in 0..samples {
+        let token = reader.next_token().ok_or_else(|| format!("missing {}", describe_sample(index, width)))?;
+        let value = token.parse::<usize>()
+            .map_err(|_| format!("invalid {} '{}'", describe_sample(index, width), token))?;
+        check_sample(value, index, width, maxval)?;
+        values.push(value);
+    }
+
+    return Ok(values);
+}
+
+fn read_binary_samples(reader: &mut PpmReader, width: usize, samples: usize, maxval: usize) -> Result<Vec<usize>, String> {
+    reader.skip_raster_separator("maxval")?;
+
+    let bytes_per_sample = if maxval < 256 { 1 } else { 2 };
+    let raster = reader.remaining();
+    let expected = samples * bytes_per_sample;
+    if raster.len() < expected {
+        return Err(format!("truncated pixel data, expected {} bytes but found {}", expected, raster.len()));
+    }
+
+    let mut values = Vec::with_capacity(samples);
+    for (index, sample) in raster[..expected].chunks(bytes_per_sample).enumerate() {
+        let value = if bytes_per_sample == 1 {
+            sample[0] as usize
+        } else {
+            u16::from_be_bytes([sample[0], sample[1]]) as usize
+        };
+        check_sample(value, index, width, maxval)?;
+        values.push(value);
+    }
+
+    return Ok(values);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use crate::ppm::{PpmFormat, quantize};
+    use crate::canvas::Canvas;
+    use crate::canvas::tests::test_canvas;
+    use crate::color::Color;
+
+    macro_rules! quantize_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (value, maxval, expected) = $value;
+
+                assert_eq!(expected, quantize(value, maxval));
+            }
+        )*
+        }
+    }
+
+    quantize_tests! {
+        quantize_zero: (0.0, 255, 0),
+        quantize_one_is_maxval: (1.0, 255, 255),
+        quantize_rounds_half_up: (0.5, 255, 128),
+        quantize_rounds_down: (0.501 / 255.0, 255, 1),
+        quantize_clamps_above_one: (1.5, 255, 255),
+        quantize_clamps_below_zero: (-0.5, 255, 0),
+        quantize_nan_is_zero: (f64::NAN, 255, 0),
+        quantize_16_bit_one: (1.0, 65535, 65535),
+        quantize_16_bit_half: (0.5, 65535, 32768),
+        quantize_16_bit_clamps: (2.0, 65535, 65535),
+    }
+
+    #[test]
+    fn writing_a_binary_ppm() {
+        let mut out = Vec::new();
+
+        test_canvas().write_ppm(&mut out, PpmFormat::Binary, 255).unwrap();
+
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(header, &out[..header.len()]);
+        assert_eq!(vec!(255, 0, 128, 0, 255, 0, 0, 0, 255, 51, 102, 153), out[header.len()..].to_vec());
+    }
+
+    #[test]
+    fn writing_a_16_bit_binary_ppm() {
+        let mut out = Vec::new();
+
+        test_canvas().write_ppm(&mut out, PpmFormat::Binary, 65535).unwrap();
+
+        let header = b"P6\n2 2\n65535\n";
+        assert_eq!(header, &out[..header.len()]);
+        let samples = &out[header.len()..];
+        assert_eq!(2 * 2 * 3 * 2, samples.len());
+        // big-endian, most significant byte first
+        assert_eq!(vec!(0xff, 0xff, 0x00, 0x00, 0x80, 0x00), samples[..6].to_vec());
+        assert_eq!(vec!(0x33, 0x33, 0x66, 0x66, 0x99, 0x99), samples[18..].to_vec());
+    }
+
+    #[test]
+    fn writing_a_16_bit_plain_ppm() {
+        let mut out = Vec::new();
+
+        test_canvas().write_ppm(&mut out, PpmFormat::Plain, 65535).unwrap();
+
+        let ppm = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = ppm.lines().collect();
+        assert_eq!("P3", lines[0]);
+        assert_eq!("65535", lines[2]);
+        assert_eq!("65535 0 32768 0 65535 0", lines[3]);
+        assert_eq!("0 0 65535 13107 26214 39321", lines[4]);
+    }
+
+    #[test]
+    fn plain_16_bit_lines_are_wrapped_at_70_characters() {
+        let mut c = Canvas::new(10, 1);
+        for x in 0..c.width() {
+            c.write_pixel(x, 0, Color::new(1.0, 1.0, 1.0));
+        }
+        let mut out = Vec::new();
+
+        c.write_ppm(&mut out, PpmFormat::Plain, 65535).unwrap();
+
+        let ppm = String::from_utf8(out).unwrap();
+        let pixel_lines: Vec<&str> = ppm.lines().skip(3).collect();
+        assert!(pixel_lines.iter().all(|line| line.len() <= 70));
+        assert_eq!(30, pixel_lines.iter().map(|line| line.split(' ').count()).sum::<usize>());
+        assert_eq!(11, pixel_lines[0].split(' ').count());
+    }
+
+    #[test]
+    fn a_zero_maxval_is_rejected() {
+        let mut out = Vec::new();
+
+        let result = test_canvas().write_ppm(&mut out, PpmFormat::Plain, 0);
+
+        assert_eq!(io::ErrorKind::InvalidInput, result.unwrap_err().kind());
+    }
+
+    #[test]
+    fn reading_a_file_with_the_wrong_magic_number() {
+        let ppm = b"P32\n1 1\n255\n0 0 0\n";
+
+        assert_eq!(Err("unsupported magic number 'P32', expected P3 or P6".to_string()), Canvas::from_ppm(ppm));
+    }
+
+    #[test]
+    fn reading_an_empty_file() {
+        assert_eq!(Err("empty file, expected the P3 or P6 magic number".to_string()), Canvas::from_ppm(b""));
+    }
+
+    #[test]
+    fn reading_a_ppm_returns_a_canvas_of_the_right_size() {
+        let ppm = "P3\n10 2\n255\n".to_string() + &"0 0 0 ".repeat(20);
+
+        let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+
+        assert_eq!(10, canvas.width());
+        assert_eq!(2, canvas.height());
+    }
+
+    macro_rules! reading_pixel_data_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (x, y, expected) = $value;
+                let ppm = b"P3\n4 3\n255\n\
+                    255 127 0  0 127 255  127 255 0  255 255 255\n\
+                    0 0 0  255 0 0  0 255 0  0 0 255\n\
+                    255 255 0  0 255 255  255 0 255  127 127 127\n";
+
+                let canvas = Canvas::from_ppm(ppm).unwrap();
+
+                assert_eq!(expected, canvas.pixel_at(x, y).round());
+            }
+        )*
+        }
+    }
+
+    reading_pixel_data_tests! {
+        reading_pixel_data_0_0: (0, 0, Color::new(1.0, 0.49804, 0.0)),
+        reading_pixel_data_1_0: (1, 0, Color::new(0.0, 0.49804, 1.0)),
+        reading_pixel_data_2_0: (2, 0, Color::new(0.49804, 1.0, 0.0)),
+        reading_pixel_data_3_0: (3, 0, Color::new(1.0, 1.0, 1.0)),
+        reading_pixel_data_0_1: (0, 1, Color::new(0.0, 0.0, 0.0)),
+        reading_pixel_data_1_1: (1, 1, Color::new(1.0, 0.0, 0.0)),
+        reading_pixel_data_2_1: (2, 1, Color::new(0.0, 1.0, 0.0)),
+        reading_pixel_data_3_1: (3, 1, Color::new(0.0, 0.0, 1.0)),
+        reading_pixel_data_0_2: (0, 2, Color::new(1.0, 1.0, 0.0)),
+        reading_pixel_data_1_2: (1, 2, Color::new(0.0, 1.0, 1.0)),
+        reading_pixel_data_2_2: (2, 2, Color::new(1.0, 0.0, 1.0)),
+        reading_pixel_data_3_2: (3, 2, Color::new(0.49804, 0.49804, 0.49804)),
+    }
+
+    #[test]
+    fn ppm_parsing_ignores_comment_lines() {
+        let ppm = b"P3\n# this is a comment\n2 1\n# this, too\n255\n# another comment\n255 255 255\n# oh, no, comments in the pixel data!\n255 0 255\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(Color::new(1.0, 1.0, 1.0), canvas.pixel_at(0, 0));
+        assert_eq!(Color::new(1.0, 0.0, 1.0), canvas.pixel_at(1, 0));
+    }
+
+    #[test]
+    fn ppm_parsing_allows_comments_and_any_whitespace_inside_the_header() {
+        let ppm = b"P3# magic\n\t2\r\n\x0b1#width and height\n\x0c255 255 255 255\t0 0 0";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(2, canvas.width());
+        assert_eq!(Color::new(1.0, 1.0, 1.0), canvas.pixel_at(0, 0));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), canvas.pixel_at(1, 0));
+    }
+
+    #[test]
+    fn ppm_parsing_allows_an_rgb_triple_to_span_lines() {
+        let ppm = b"P3\n1 1\n255\n51\n153\n\n204\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(Color::new(0.2, 0.6, 0.8), canvas.pixel_at(0, 0));
+    }
+
+    #[test]
+    fn ppm_parsing_respects_the_scale_setting() {
+        let ppm = b"P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(Color::new(0.75, 0.5, 0.25), canvas.pixel_at(0, 1));
+    }
+
+    #[test]
+    fn reading_a_binary_ppm() {
+        let mut ppm = b"P6\n# binary\n2 1\n255\n".to_vec();
+        ppm.extend_from_slice(&[255, 0, 51, 10, 35, 32]);
+
+        let canvas = Canvas::from_ppm(&ppm).unwrap();
+
+        assert_eq!(Color::new(1.0, 0.0, 0.2), canvas.pixel_at(0, 0));
+        // sample bytes that look like whitespace or comments are still pixel data
+        assert_eq!(Color::new(10.0 / 255.0, 35.0 / 255.0, 32.0 / 255.0), canvas.pixel_at(1, 0));
+    }
+
+    #[test]
+    fn reading_a_16_bit_binary_ppm() {
+        let mut ppm = b"P6 1 1 65535\n".to_vec();
+        ppm.extend_from_slice(&[0xff, 0xff, 0x80, 0x00, 0x00, 0x01]);
+
+        let canvas = Canvas::from_ppm(&ppm).unwrap();
+
+        assert_eq!(Color::new(1.0, 32768.0 / 65535.0, 1.0 / 65535.0), canvas.pixel_at(0, 0));
+    }
+
+    #[test]
+    fn reading_a_16_bit_plain_ppm() {
+        let ppm = b"P3\n1 1\n65535\n65535 0 13107\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(Color::new(1.0, 0.0, 0.2), canvas.pixel_at(0, 0));
+    }
+
+    macro_rules! round_trip_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (format, maxval) = $value;
+                let original = test_canvas();
+                let mut out = Vec::new();
+                original.write_ppm(&mut out, format, maxval).unwrap();
+
+                let canvas = Canvas::from_ppm(&out).unwrap();
+
+                for y in 0..original.height() {
+                    for x in 0..original.width() {
+                        let (e, a) = (original.pixel_at(x, y), canvas.pixel_at(x, y));
+                        for (expected, actual) in [e.red, e.green, e.blue].iter().zip([a.red, a.green, a.blue].iter()) {
+                            assert_eq!(quantize(*expected, maxval), quantize(*actual, maxval));
+                        }
+                    }
+                }
+            }
+        )*
+        }
+    }
+
+    round_trip_tests! {
+        round_trip_plain_8_bit: (PpmFormat::Plain, 255),
+        round_trip_binary_8_bit: (PpmFormat::Binary, 255),
+        round_trip_plain_16_bit: (PpmFormat::Plain, 65535),
+        round_trip_binary_16_bit: (PpmFormat::Binary, 65535),
+        round_trip_binary_small_maxval: (PpmFormat::Binary, 15),
+    }
+
+    macro_rules! malformed_ppm_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (ppm, expected): (&[u8], &str) = $value;
+
+                assert_eq!(Err(expected.to_string()), Canvas::from_ppm(ppm));
+            }
+        )*
+        }
+    }
+
+    malformed_ppm_tests! {
+        ppm_parsing_fails_on_missing_pixel_data: (b"P3\n2 1\n255\n255 255 255\n", "missing red sample of pixel (1, 0)"),
+        ppm_parsing_fails_on_a_missing_height: (b"P3\n2\n", "missing height"),
+        ppm_parsing_fails_on_an_invalid_width: (b"P3\nwide 1\n255\n", "invalid width 'wide'"),
+        ppm_parsing_fails_on_a_zero_maxval: (b"P3\n1 1\n0\n0 0 0\n", "invalid maxval '0', expected 1 to 65535"),
+        ppm_parsing_fails_on_a_too_large_maxval: (b"P3\n1 1\n65536\n0 0 0\n", "invalid maxval '65536', expected 1 to 65535"),
+        ppm_parsing_fails_on_an_invalid_sample: (b"P3\n1 1\n255\n0 x 0\n", "invalid green sample of pixel (0, 0) 'x'"),
+        ppm_parsing_fails_on_a_sample_above_maxval: (b"P3\n1 1\n100\n0 0 101\n", "blue sample of pixel (0, 0) is 101, larger than maxval 100"),
+        ppm_parsing_fails_on_a_huge_plain_image: (b"P3\n100000 100000\n255\n0 0 0\n", "truncated pixel data, 30000000000 samples need more than the 7 bytes left"),
+        ppm_parsing_fails_on_an_overflowing_size: (b"P3\n18446744073709551615 2\n255\n", "image size 18446744073709551615x2 is too large"),
+        ppm_parsing_fails_on_truncated_binary_data: (b"P6\n2 1\n255\n\x01\x02\x03\x04", "truncated pixel data, expected 6 bytes but found 4"),
+        ppm_parsing_fails_on_truncated_16_bit_binary_data: (b"P6\n1 1\n65535\n\x01\x02\x03\x04\x05", "truncated pixel data, expected 6 bytes but found 5"),
+        ppm_parsing_fails_on_a_binary_sample_above_maxval: (b"P6\n1 1\n100\n\x00\x65\x00", "green sample of pixel (0, 0) is 101, larger than maxval 100"),
+        ppm_parsing_fails_without_whitespace_before_binary_data: (b"P6\n1 1\n255", "expected a single whitespace character after the maxval"),
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            return Err(io::Error::other("disk full"));
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    #[test]
+    fn write_errors_are_returned() {
+        let result = test_canvas().write_ppm(&mut FailingWriter, PpmFormat::Binary, 255);
+
+        assert_eq!("disk full", result.unwrap_err().to_string());
+    }
+}
@@ -1,9 +1,10 @@
 use crate::tuple::Tuple;
-use crate::sphere::Sphere;
-use crate::intersection::{Intersection, Intersections};
+use crate::intersection::Intersections;
 use crate::matrix::Matrix4;
+use crate::shape::Shape;
 
 #[derive(Debug)]
+#[derive(Copy, Clone)]
 #[derive(PartialEq)]
 pub struct Ray {
     pub origin: Tuple,
@@ -11,41 +12,14 @@ pub struct Ray {
 }
 
 impl Ray {
-    #[allow(dead_code)]
     pub fn position(&self, t: f64) -> Tuple {
         return self.origin + self.direction * t;
     }
 
-    pub fn intersect(&self, sphere: Sphere) -> Intersections {
-        let inv_sphere_transform = sphere.transform.inverse();
-        let transformed_ray = self.transform(inv_sphere_transform);
+    pub fn intersect<'a>(&self, shape: &'a dyn Shape) -> Intersections<'a> {
+        let local_ray = self.transform(shape.transform().inverse());
 
-        let (a, b, c) = Ray::calculate_intersections(&transformed_ray);
-
-        let discriminate = (b * b) - (4.0 * a * c);
-
-        if discriminate < 0.0 {
-            return Intersections::new();
-        }
-
-        let t1 = (-b - discriminate.sqrt()) / (2.0 * a);
-        let t2 = (-b + discriminate.sqrt()) / (2.0 * a);
-
-        return Intersections {
-            values: vec!(
-                Intersection { t: t1, object: sphere },
-                Intersection { t: t2, object: sphere }
-            )
-        };
-    }
-
-    fn calculate_intersections(original_ray: &Ray) -> (f64, f64, f64) {
-        let sphere_to_ray = original_ray.origin - Tuple::point(0.0, 0.0, 0.0);
-
-        let a = original_ray.direction.dot(original_ray.direction);
-        let b = 2.0 * original_ray.direction.dot(sphere_to_ray);
-        let c = sphere_to_ray.dot(sphere_to_ray) - 1.0;
-        (a, b, c)
+        return shape.local_intersect(&local_ray);
     }
 
     pub fn transform(&self, m: Matrix4) -> Ray {
@@ -112,13 +86,13 @@ mod tests {
                 let (origin, expected) = $value;
 
                 let r = Ray {
-                        origin: origin,
+                        origin,
                         direction: Tuple::vector(0.0, 0.0, 1.0)
                 };
 
                 let s = Sphere::new();
 
-                let actual = r.intersect(s);
+                let actual = r.intersect(&s);
 
                 let actual_points:Vec<f64> = actual.values.into_iter().map(|f| f.t).collect();
                 assert_eq!(expected, actual_points);
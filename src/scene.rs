@@ -0,0 +1,1049 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use yaml_rust::{Yaml, YamlLoader};
+use crate::camera::Camera;
+use crate::world::World;
+use crate::light::PointLight;
+use crate::color::Color;
+use crate::tuple::Tuple;
+use crate::matrix::Matrix4;
+use crate::material::Material;
+use crate::shape::Shape;
+use crate::sphere::Sphere;
+use crate::plane::Plane;
+use crate::cube::Cube;
+use crate::cylinder::Cylinder;
+use crate::cone::Cone;
+use crate::triangle::Triangle;
+use crate::group::Group;
+use crate::csg::{Csg, CsgOperation};
+use crate::obj_file::ObjFile;
+use crate::pattern::{Pattern, SolidPattern, StripePattern, GradientPattern, RingPattern, CheckersPattern,
+                     BlendPattern, PerturbedPattern};
+use crate::texture::{UvPattern, UvCheckers, UvImage, UvMapping, TextureMapPattern};
+use crate::display::{DisplayTransform, ToneMapping};
+
+// a scene file is a YAML list of entries, each one of:
+//   - add: camera      width, height, field-of-view, from, to, up
+//   - add: light       at, intensity
+//   - add: display     exposure, tone-mapping (clamp, reinhard, extended-reinhard with white, aces), srgb,
+//                      bit-depth (8 or 16, for PPM and PNG) and ppm (plain or binary)
+//   - define: <name>   value, and optionally extend: <name> to start from another definition
+//   - add: <shape>     material, transform and the shape's own properties
+// materials, patterns, transforms and shapes can refer to a definition by name
+// shapes inside a group, csg or obj without a material of their own take the enclosing one
+pub struct Scene {
+    pub camera: Camera,
+    pub world: World,
+    pub display: DisplayTransform,
+}
+
+// entry is the 1-based position of the offending entry in the file, 0 when it is about the file as a whole
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct SceneError {
+    pub entry: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.entry == 0 {
+            return write!(f, "{}", self.message);
+        }
+
+        return write!(f, "entry {}: {}", self.entry, self.message);
+    }
+}
+
+impl Scene {
+    #[allow(dead_code)]
+    pub fn parse(input: &str) -> Result<Scene, SceneError> {
+        return SceneLoader::new(PathBuf::new()).load(input);
+    }
+
+    // files the scene refers to, like meshes and textures, are looked up next to it
+    pub fn from_file(file_name: &str) -> Result<Scene, String> {
+        let contents = std::fs::read_to_string(file_name)
+            .map_err(|why| format!("couldn't read {}: {}", file_name, why))?;
+        let base_dir = Path::new(file_name).parent().map_or(PathBuf::new(), |dir| dir.to_path_buf());
+
+        return SceneLoader::new(base_dir).load(&contents)
+            .map_err(|why| format!("couldn't load {}: {}", file_name, why));
+    }
+}
+
+type PatternPair = (Rc<dyn Pattern>, Rc<dyn Pattern>);
+
+struct SceneLoader {
+    base_dir: PathBuf,
+    defines: HashMap<String, Yaml>,
+    // definitions being expanded right now, a name that shows up again is a cycle
+    expanding: RefCell<Vec<String>>,
+}
+
+impl SceneLoader {
+    fn new(base_dir: PathBuf) -> Self {
+        return SceneLoader { base_dir, defines: HashMap::new(), expanding: RefCell::new(Vec::new()) };
+    }
+
+    fn load(&mut self, input: &str) -> Result<Scene, SceneError> {
+        let documents = YamlLoader::load_from_str(input)
+            .map_err(|why| SceneError { entry: 0, message: format!("invalid YAML: {}", why) })?;
+        let entries = match documents.first() {
+            Some(Yaml::Array(entries)) => entries.clone(),
+            Some(_) => return Err(SceneError { entry: 0, message: "expected a list of entries".to_string() }),
+            None => Vec::new(),
+        };
+
+        let mut camera = None;
+        let mut world = World::new();
+        let mut display = DisplayTransform::default();
+
+        for (index, entry) in entries.iter().enumerate() {
+            let error = |message: String| SceneError {
+                entry: index + 1,
+                message: format!("({}) {}", describe(entry), message),
+            };
+
+            if let Some(name) = entry["define"].as_str() {
+                let value = self.resolve_define(entry).map_err(error)?;
+                self.defines.insert(name.to_string(), value);
+                continue;
+            }
+
+            match entry["add"].as_str() {
+                Some("camera") => camera = Some(self.camera(entry).map_err(error)?),
+                Some("light") => world.lights.push(self.light(entry).map_err(error)?),
+                Some("display") => display = self.display(entry).map_err(error)?,
+                Some(_) => world.objects.push(self.shape(entry, &Material::default()).map_err(error)?),
+                None => return Err(error("expected an 'add' or 'define' key".to_string())),
+            }
+        }
+
+        return match camera {
+            Some(camera) => Ok(Scene { camera, world, display }),
+            None => Err(SceneError { entry: 0, message: "the scene has no camera".to_string() }),
+        };
+    }
+
+    // a definition's value, merged over the definition it extends
+    fn resolve_define(&self, entry: &Yaml) -> Result<Yaml, String> {
+        let value = &entry["value"];
+        if value.is_badvalue() {
+            return Err("missing 'value'".to_string());
+        }
+
+        let base = match &entry["extend"] {
+            Yaml::BadValue => return Ok(value.clone()),
+            Yaml::String(name) => self.define(name)?,
+            _ => return Err("'extend' must name a definition".to_string()),
+        };
+
+        return match (base, value) {
+            (Yaml::Hash(base), Yaml::Hash(overrides)) => {
+                let mut merged = base.clone();
+                for (key, value) in overrides.iter() {
+                    merged.insert(key.clone(), value.clone());
+                }
+                Ok(Yaml::Hash(merged))
+            }
+            _ => Err("only mappings can extend each other".to_string()),
+        };
+    }
+
+    fn define(&self, name: &str) -> Result<&Yaml, String> {
+        return self.defines.get(name).ok_or(format!("unknown definition '{}'", name));
+    }
+
+    // a definition name stands in for its value, build turns that value into whatever the name was used as
+    fn expand<T>(&self, name: &str, build: impl FnOnce(&Yaml) -> Result<T, String>) -> Result<T, String> {
+        if self.expanding.borrow().iter().any(|expanding| expanding == name) {
+            return Err(format!("definition '{}' refers to itself", name));
+        }
+        let definition = self.define(name)?;
+
+        self.expanding.borrow_mut().push(name.to_string());
+        let result = build(definition);
+        self.expanding.borrow_mut().pop();
+
+        return result;
+    }
+
+    fn camera(&self, entry: &Yaml) -> Result<Camera, String> {
+        let width = require_usize(entry, "width")?;
+        let height = require_usize(entry, "height")?;
+        let field_of_view = require_f64(entry, "field-of-view")?;
+
+        let mut camera = Camera::new(width, height, field_of_view);
+        camera.set_transform(Matrix4::view_transform(
+            require_point(entry, "from")?,
+            require_point(entry, "to")?,
+            require_vector(entry, "up")?,
+        ));
+
+        return Ok(camera);
+    }
+
+    fn light(&self, entry: &Yaml) -> Result<PointLight, String> {
+        return Ok(PointLight {
+            position: require_point(entry, "at")?,
+            intensity: require_color(entry, "intensity")?,
+        });
+    }
+
+    fn display(&self, entry: &Yaml) -> Result<DisplayTransform, String> {
+        let tone_mapping = match entry["tone-mapping"].as_str() {
+            None if entry["tone-mapping"].is_badvalue() => ToneMapping::Clamp,
+            Some("clamp") => ToneMapping::Clamp,
+            Some("reinhard") => ToneMapping::Reinhard,
+            Some("extended-reinhard") => {
+                let white = require_f64(entry, "white")?;
+                if white <= 0.0 {
+                    return Err("'white' must be above 0".to_string());
+                }
+                ToneMapping::ExtendedReinhard { white }
+            }
+            Some("aces") => ToneMapping::AcesFilmic,
+            Some(other) => return Err(format!("unknown tone mapping '{}'", other)),
+            None => return Err("'tone-mapping' must name a tone mapping".to_string()),
+        };
+
+        let sixteen_bit = match &entry["bit-depth"] {
+            Yaml::BadValue | Yaml::Integer(8) => false,
+            Yaml::Integer(16) => true,
+            _ => return Err("'bit-depth' must be 8 or 16".to_string()),
+        };
+        let binary_ppm = match entry["ppm"].as_str() {
+            None if entry["ppm"].is_badvalue() => false,
+            Some("plain") => false,
+            Some("binary") => true,
+            Some(other) => return Err(format!("unknown ppm encoding '{}', expected plain or binary", other)),
+            None => return Err("'ppm' must be plain or binary".to_string()),
+        };
+
+        return Ok(DisplayTransform {
+            exposure: optional_f64(entry, "exposure", 0.0)?,
+            tone_mapping,
+            srgb: optional_bool(entry, "srgb", true)?,
+            sixteen_bit,
+            binary_ppm,
+        });
+    }
+
+    // shapes without a material of their own take the one of the group or csg they are in
+    fn shape(&self, entry: &Yaml, inherited: &Material) -> Result<Box<dyn Shape>, String> {
+        let kind = entry["add"].as_str().ok_or("'add' must name a shape")?;
+        let material = match &entry["material"] {
+            Yaml::BadValue => inherited.clone(),
+            value => self.material(value).map_err(|why| format!("material: {}", why))?,
+        };
+
+        let mut shape: Box<dyn Shape> = match kind {
+            "sphere" => Box::new(Sphere::new()),
+            "plane" => Box::new(Plane::new()),
+            "cube" => Box::new(Cube::new()),
+            "cylinder" => {
+                let mut cylinder = Cylinder::new();
+                cylinder.minimum = optional_f64(entry, "min", f64::NEG_INFINITY)?;
+                cylinder.maximum = optional_f64(entry, "max", f64::INFINITY)?;
+                cylinder.closed = optional_bool(entry, "closed", false)?;
+                Box::new(cylinder)
+            }
+            "cone" => {
+                let mut cone = Cone::new();
+                cone.minimum = optional_f64(entry, "min", f64::NEG_INFINITY)?;
+                cone.maximum = optional_f64(entry, "max", f64::INFINITY)?;
+                cone.closed = optional_bool(entry, "closed", false)?;
+                Box::new(cone)
+            }
+            "triangle" => Box::new(Triangle::new(
+                require_point(entry, "p1")?,
+                require_point(entry, "p2")?,
+                require_point(entry, "p3")?,
+            )),
+            "group" => {
+                let children = entry["children"].as_vec().ok_or("missing 'children' list")?;
+                let mut group = Group::new();
+                for (index, child) in children.iter().enumerate() {
+                    let child = self.shape(child, &material).map_err(|why| format!("children[{}]: {}", index, why))?;
+                    group.add_child(child);
+                }
+                Box::new(group)
+            }
+            "csg" => {
+                let operation = match entry["operation"].as_str() {
+                    Some("union") => CsgOperation::Union,
+                    Some("intersection") => CsgOperation::Intersection,
+                    Some("difference") => CsgOperation::Difference,
+                    Some(other) => return Err(format!("unknown csg operation '{}'", other)),
+                    None => return Err("missing 'operation'".to_string()),
+                };
+                let left = self.shape(&entry["left"], &material).map_err(|why| format!("left: {}", why))?;
+                let right = self.shape(&entry["right"], &material).map_err(|why| format!("right: {}", why))?;
+                Box::new(Csg::new(operation, left, right))
+            }
+            "obj" => {
+                let file_name = entry["file"].as_str().ok_or("missing 'file'")?;
+                let path = self.base_dir.join(file_name);
+                Box::new(ObjFile::from_file(&path.to_string_lossy())?.to_group(&material))
+            }
+            name => {
+                // a defined shape, with this entry's keys laid over it
+                return self.expand(name, |definition| match definition {
+                    Yaml::Hash(definition) => {
+                        let mut merged = definition.clone();
+                        for (key, value) in entry.as_hash().unwrap().iter() {
+                            if key.as_str() != Some("add") {
+                                merged.insert(key.clone(), value.clone());
+                            }
+                        }
+                        self.shape(&Yaml::Hash(merged), inherited)
+                    }
+                    _ => Err(format!("'{}' is not a shape definition", name)),
+                });
+            }
+        };
+
+        if !entry["transform"].is_badvalue() {
+            shape.set_transform(self.transform(&entry["transform"]).map_err(|why| format!("transform: {}", why))?);
+        }
+        shape.set_material(material);
+
+        return Ok(shape);
+    }
+
+    // each step is applied after the ones before it, e.g. [[scale, 2, 2, 2], [translate, 0, 1, 0]]
+    fn transform(&self, value: &Yaml) -> Result<Matrix4, String> {
+        if let Yaml::String(name) = value {
+            return self.expand(name, |definition| self.transform(definition));
+        }
+        let steps = value.as_vec().ok_or("expected a list of transformations")?;
+
+        let mut transform = Matrix4::identity();
+        for (index, step) in steps.iter().enumerate() {
+            transform = match step {
+                Yaml::String(_) => self.transform(step)? * transform,
+                _ => self.transform_step(step, transform).map_err(|why| format!("step {}: {}", index + 1, why))?,
+            };
+        }
+
+        return Ok(transform);
+    }
+
+    fn transform_step(&self, step: &Yaml, transform: Matrix4) -> Result<Matrix4, String> {
+        let parts = step.as_vec().ok_or("expected [operation, arguments...]")?;
+        let operation = parts.first().and_then(|part| part.as_str()).ok_or("missing the operation name")?;
+        let args = parts[1..].iter()
+            .map(|part| as_f64(part).ok_or(format!("invalid number in '{}'", operation)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        let expect = |count: usize| -> Result<(), String> {
+            if args.len() != count {
+                return Err(format!("'{}' takes {} numbers, found {}", operation, count, args.len()));
+            }
+            return Ok(());
+        };
+
+        return match operation {
+            "translate" => expect(3).map(|_| transform.translate(args[0], args[1], args[2])),
+            "scale" => expect(3).map(|_| transform.scale(args[0], args[1], args[2])),
+            "rotate-x" => expect(1).map(|_| transform.rotate_x(args[0])),
+            "rotate-y" => expect(1).map(|_| transform.rotate_y(args[0])),
+            "rotate-z" => expect(1).map(|_| transform.rotate_z(args[0])),
+            "shear" => expect(6).map(|_| transform.shear(args[0], args[1], args[2], args[3], args[4], args[5])),
+            _ => Err(format!("unknown transformation '{}'", operation)),
+        };
+    }
+
+    fn material(&self, value: &Yaml) -> Result<Material, String> {
+        if let Yaml::String(name) = value {
+            return self.expand(name, |definition| self.material(definition));
+        }
+        let properties = value.as_hash().ok_or("expected a mapping of material properties")?;
+
+        let mut material = Material::default();
+        for key in properties.keys() {
+            let key = key.as_str().ok_or("property names must be strings")?;
+            match key {
+                "color" => material.color = require_color(value, key)?,
+                "ambient" => material.ambient = require_f64(value, key)?,
+                "diffuse" => material.diffuse = require_f64(value, key)?,
+                "specular" => material.specular = require_f64(value, key)?,
+                "shininess" => material.shininess = require_f64(value, key)?,
+                "reflective" => material.reflective = require_f64(value, key)?,
+                "transparency" => material.transparency = require_f64(value, key)?,
+                "refractive-index" => material.refractive_index = require_f64(value, key)?,
+                "pattern" => material.pattern = Some(self.pattern(&value[key]).map_err(|why| format!("pattern: {}", why))?),
+                _ => return Err(format!("unknown material property '{}'", key)),
+            }
+        }
+
+        return Ok(material);
+    }
+
+    fn pattern(&self, value: &Yaml) -> Result<Rc<dyn Pattern>, String> {
+        if let Yaml::String(name) = value {
+            return self.expand(name, |definition| self.pattern(definition));
+        }
+        let kind = value["type"].as_str().ok_or("missing pattern 'type'")?;
+
+        let mut pattern: Box<dyn Pattern> = match kind {
+            "solid" => Box::new(SolidPattern::new(require_color(value, "color")?)),
+            "stripes" => {
+                let (a, b) = self.pattern_pair(value)?;
+                Box::new(StripePattern::nested(a, b))
+            }
+            "gradient" => {
+                let (a, b) = self.pattern_pair(value)?;
+                Box::new(GradientPattern::nested(a, b))
+            }
+            "rings" => {
+                let (a, b) = self.pattern_pair(value)?;
+                Box::new(RingPattern::nested(a, b))
+            }
+            "checkers" => {
+                let (a, b) = self.pattern_pair(value)?;
+                Box::new(CheckersPattern::nested(a, b))
+            }
+            "blend" => {
+                let (a, b) = self.pattern_pair(value)?;
+                Box::new(BlendPattern::with_weight(a, b, optional_f64(value, "weight", 0.5)?))
+            }
+            "perturb" => {
+                let child = self.pattern(&value["pattern"]).map_err(|why| format!("pattern: {}", why))?;
+                Box::new(PerturbedPattern::new(child, optional_f64(value, "scale", 0.2)?))
+            }
+            "map" => {
+                let mapping = match value["mapping"].as_str() {
+                    Some("spherical") => UvMapping::Spherical,
+                    Some("planar") => UvMapping::Planar,
+                    Some("cylindrical") => UvMapping::Cylindrical,
+                    Some("cube") => UvMapping::Cube,
+                    Some(other) => return Err(format!("unknown uv mapping '{}'", other)),
+                    None => return Err("missing 'mapping'".to_string()),
+                };
+                let uv_pattern = self.uv_pattern(&value["uv_pattern"]).map_err(|why| format!("uv_pattern: {}", why))?;
+                Box::new(TextureMapPattern::new(uv_pattern, mapping))
+            }
+            _ => return Err(format!("unknown pattern type '{}'", kind)),
+        };
+
+        if !value["transform"].is_badvalue() {
+            pattern.set_transform(self.transform(&value["transform"]).map_err(|why| format!("transform: {}", why))?);
+        }
+
+        return Ok(Rc::from(pattern));
+    }
+
+    // the two halves of a two-part pattern, given either as 'colors' or as nested 'patterns'
+    fn pattern_pair(&self, value: &Yaml) -> Result<PatternPair, String> {
+        if let Some(colors) = value["colors"].as_vec() {
+            if colors.len() != 2 {
+                return Err(format!("'colors' needs 2 colors, found {}", colors.len()));
+            }
+            let a = as_color(&colors[0]).ok_or("'colors' must hold [r, g, b] triples")?;
+            let b = as_color(&colors[1]).ok_or("'colors' must hold [r, g, b] triples")?;
+            return Ok((Rc::new(SolidPattern::new(a)), Rc::new(SolidPattern::new(b))));
+        }
+
+        if let Some(patterns) = value["patterns"].as_vec() {
+            if patterns.len() != 2 {
+                return Err(format!("'patterns' needs 2 patterns, found {}", patterns.len()));
+            }
+            let a = self.pattern(&patterns[0]).map_err(|why| format!("patterns[0]: {}", why))?;
+            let b = self.pattern(&patterns[1]).map_err(|why| format!("patterns[1]: {}", why))?;
+            return Ok((a, b));
+        }
+
+        return Err("expected 'colors' or 'patterns'".to_string());
+    }
+
+    fn uv_pattern(&self, value: &Yaml) -> Result<Rc<dyn UvPattern>, String> {
+        if let Yaml::String(name) = value {
+            return self.expand(name, |definition| self.uv_pattern(definition));
+        }
+
+        return match value["type"].as_str() {
+            Some("checkers") => {
+                let colors = value["colors"].as_vec().filter(|colors| colors.len() == 2)
+                    .ok_or("'colors' needs 2 colors")?;
+                let a = as_color(&colors[0]).ok_or("'colors' must hold [r, g, b] triples")?;
+                let b = as_color(&colors[1]).ok_or("'colors' must hold [r, g, b] triples")?;
+                Ok(Rc::new(UvCheckers::new(require_f64(value, "width")?, require_f64(value, "height")?, a, b)))
+            }
+            Some("image") => {
+                let file_name = value["file"].as_str().ok_or("missing 'file'")?;
+                let path = self.base_dir.join(file_name);
+                Ok(Rc::new(UvImage::from_file(&path.to_string_lossy())?))
+            }
+            Some(other) => Err(format!("unknown uv pattern type '{}'", other)),
+            None => Err("missing uv pattern 'type'".to_string()),
+        };
+    }
+}
+
+// how an entry is named in error messages, e.g. "add: sphere"
+fn describe(entry: &Yaml) -> String {
+    if let Some(name) = entry["define"].as_str() {
+        return format!("define: {}", name);
+    }
+    if let Some(kind) = entry["add"].as_str() {
+        return format!("add: {}", kind);
+    }
+
+    return "unnamed entry".to_string();
+}
+
+// YAML tells 1 and 1.0 apart, the scene format does not
+fn as_f64(value: &Yaml) -> Option<f64> {
+    return match value {
+        Yaml::Real(_) => value.as_f64(),
+        Yaml::Integer(i) => Some(*i as f64),
+        _ => None,
+    };
+}
+
+fn as_triple(value: &Yaml) -> Option<(f64, f64, f64)> {
+    let values = value.as_vec().filter(|values| values.len() == 3)?;
+
+    return Some((as_f64(&values[0])?, as_f64(&values[1])?, as_f64(&values[2])?));
+}
+
+fn as_color(value: &Yaml) -> Option<Color> {
+    return as_triple(value).map(|(r, g, b)| Color::new(r, g, b));
+}
+
+fn require_f64(entry: &Yaml, key: &str) -> Result<f64, String> {
+    return match &entry[key] {
+        Yaml::BadValue => Err(format!("missing '{}'", key)),
+        value => as_f64(value).ok_or(format!("'{}' must be a number", key)),
+    };
+}
+
+fn optional_f64(entry: &Yaml, key: &str, default: f64) -> Result<f64, String> {
+    return match &entry[key] {
+        Yaml::BadValue => Ok(default),
+        _ => require_f64(entry, key),
+    };
+}
+
+fn optional_bool(entry: &Yaml, key: &str, default: bool) -> Result<bool, String> {
+    return match &entry[key] {
+        Yaml::BadValue => Ok(default),
+        value => value.as_bool().ok_or(format!("'{}' must be true or false", key)),
+    };
+}
+
+fn require_usize(entry: &Yaml, key: &str) -> Result<usize, String> {
+    return match &entry[key] {
+        Yaml::BadValue => Err(format!("missing '{}'", key)),
+        value => value.as_i64().filter(|i| *i > 0).map(|i| i as usize)
+            .ok_or(format!("'{}' must be a positive whole number", key)),
+    };
+}
+
+fn require_triple(entry: &Yaml, key: &str) -> Result<(f64, f64, f64), String> {
+    return match &entry[key] {
+        Yaml::BadValue => Err(format!("missing '{}'", key)),
+        value => as_triple(value).ok_or(format!("'{}' must be a list of 3 numbers", key)),
+    };
+}
+
+fn require_point(entry: &Yaml, key: &str) -> Result<Tuple, String> {
+    return require_triple(entry, key).map(|(x, y, z)| Tuple::point(x, y, z));
+}
+
+fn require_vector(entry: &Yaml, key: &str) -> Result<Tuple, String> {
+    return require_triple(entry, key).map(|(x, y, z)| Tuple::vector(x, y, z));
+}
+
+fn require_color(entry: &Yaml, key: &str) -> Result<Color, String> {
+    return require_triple(entry, key).map(|(r, g, b)| Color::new(r, g, b));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use crate::scene::{Scene, SceneError};
+    use crate::color::Color;
+    use crate::tuple::Tuple;
+    use crate::matrix::Matrix4;
+    use crate::ray::Ray;
+    use crate::light::PointLight;
+    use crate::display::{DisplayTransform, ToneMapping};
+
+    const CAMERA: &str = "
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [0, 1.5, -5]
+  to: [0, 1, 0]
+  up: [0, 1, 0]
+";
+
+    fn parse(entries: &str) -> Result<Scene, SceneError> {
+        return Scene::parse(&(CAMERA.to_string() + entries));
+    }
+
+    #[test]
+    fn parsing_a_camera() {
+        let scene = parse("").unwrap();
+
+        assert_eq!(100, scene.camera.hsize());
+        assert_eq!(50, scene.camera.vsize());
+        assert_eq!(0.785, scene.camera.field_of_view());
+        assert_eq!(Matrix4::view_transform(
+            Tuple::point(0.0, 1.5, -5.0),
+            Tuple::point(0.0, 1.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        ), scene.camera.transform);
+    }
+
+    #[test]
+    fn parsing_lights() {
+        let scene = parse("
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 0.5, 0.25]
+- add: light
+  at: [1, 2, 3]
+  intensity: [1, 1, 1]
+").unwrap();
+
+        assert_eq!(vec!(
+            PointLight { position: Tuple::point(-10.0, 10.0, -10.0), intensity: Color::new(1.0, 0.5, 0.25) },
+            PointLight { position: Tuple::point(1.0, 2.0, 3.0), intensity: Color::new(1.0, 1.0, 1.0) },
+        ), scene.world.lights);
+    }
+
+    #[test]
+    fn parsing_a_shape_with_a_material_and_a_transform() {
+        let scene = parse("
+- add: sphere
+  material:
+    color: [1, 0.2, 1]
+    diffuse: 0.7
+    reflective: 0.3
+    refractive-index: 1.5
+  transform:
+    - [scale, 0.5, 0.5, 0.5]
+    - [translate, 1.5, 0.5, -0.5]
+").unwrap();
+
+        let sphere = &scene.world.objects[0];
+        assert_eq!(Color::new(1.0, 0.2, 1.0), sphere.material().color);
+        assert_eq!(0.7, sphere.material().diffuse);
+        assert_eq!(0.3, sphere.material().reflective);
+        assert_eq!(1.5, sphere.material().refractive_index);
+        assert_eq!(0.9, sphere.material().specular);
+        assert_eq!(Matrix4::identity().scale(0.5, 0.5, 0.5).translate(1.5, 0.5, -0.5), sphere.transform());
+    }
+
+    #[test]
+    fn transforms_are_applied_in_the_order_they_are_listed() {
+        let scene = parse("
+- add: cube
+  transform:
+    - [rotate-x, 1.5707963267948966]
+    - [rotate-y, 0.5]
+    - [rotate-z, 0.25]
+    - [shear, 1, 0, 0, 0, 0, 0]
+    - [scale, 5, 5, 5]
+    - [translate, 10, 5, 7]
+").unwrap();
+
+        let expected = Matrix4::identity()
+            .rotate_x(PI / 2.0)
+            .rotate_y(0.5)
+            .rotate_z(0.25)
+            .shear(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0);
+        assert_eq!(expected, scene.world.objects[0].transform());
+    }
+
+    #[test]
+    fn every_primitive_can_be_added() {
+        let scene = parse("
+- add: sphere
+- add: plane
+- add: cube
+- add: cylinder
+- add: cone
+- add: triangle
+  p1: [0, 1, 0]
+  p2: [-1, 0, 0]
+  p3: [1, 0, 0]
+").unwrap();
+
+        assert_eq!(6, scene.world.objects.len());
+    }
+
+    #[test]
+    fn parsing_a_truncated_closed_cylinder() {
+        let scene = parse("
+- add: cylinder
+  min: 0
+  max: 1
+  closed: true
+").unwrap();
+        let r = Ray {
+            origin: Tuple::point(0.0, 3.0, 0.0),
+            direction: Tuple::vector(0.0, -1.0, 0.0),
+        };
+
+        let xs = scene.world.intersect_world(&r);
+
+        assert_eq!(2, xs.len());
+        assert_eq!(2.0, xs[0].t);
+        assert_eq!(3.0, xs[1].t);
+    }
+
+    #[test]
+    fn named_materials_can_extend_each_other() {
+        let scene = parse("
+- define: white-material
+  value:
+    color: [1, 1, 1]
+    diffuse: 0.7
+    specular: 0.0
+- define: blue-material
+  extend: white-material
+  value:
+    color: [0.537, 0.831, 0.914]
+- add: sphere
+  material: blue-material
+").unwrap();
+
+        let material = scene.world.objects[0].material();
+        assert_eq!(Color::new(0.537, 0.831, 0.914), material.color);
+        assert_eq!(0.7, material.diffuse);
+        assert_eq!(0.0, material.specular);
+    }
+
+    #[test]
+    fn named_transforms_can_be_used_in_a_transform_chain() {
+        let scene = parse("
+- define: standard-transform
+  value:
+    - [translate, 1, -1, 1]
+    - [scale, 0.5, 0.5, 0.5]
+- add: cube
+  transform:
+    - standard-transform
+    - [translate, 4, 0, 0]
+").unwrap();
+
+        let expected = Matrix4::identity()
+            .translate(1.0, -1.0, 1.0)
+            .scale(0.5, 0.5, 0.5)
+            .translate(4.0, 0.0, 0.0);
+        assert_eq!(expected, scene.world.objects[0].transform());
+    }
+
+    #[test]
+    fn named_shapes_can_be_added_with_overrides() {
+        let scene = parse("
+- define: glass-ball
+  value:
+    add: sphere
+    material:
+      transparency: 1
+      refractive-index: 1.5
+- add: glass-ball
+  transform:
+    - [translate, 0, 1, 0]
+").unwrap();
+
+        let ball = &scene.world.objects[0];
+        assert_eq!(1.0, ball.material().transparency);
+        assert_eq!(Matrix4::translation(0.0, 1.0, 0.0), ball.transform());
+    }
+
+    #[test]
+    fn parsing_a_pattern() {
+        let scene = parse("
+- add: plane
+  material:
+    pattern:
+      type: checkers
+      colors: [[1, 1, 1], [0, 0, 0]]
+      transform:
+        - [scale, 2, 2, 2]
+").unwrap();
+
+        let object = scene.world.objects[0].as_ref();
+        let pattern = object.material().pattern.clone().unwrap();
+        assert_eq!(Color::new(1.0, 1.0, 1.0), pattern.pattern_at_shape(object, Tuple::point(1.5, 0.0, 0.0)));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), pattern.pattern_at_shape(object, Tuple::point(2.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn parsing_nested_and_composite_patterns() {
+        let scene = parse("
+- define: red
+  value:
+    type: solid
+    color: [1, 0, 0]
+- add: plane
+  material:
+    pattern:
+      type: stripes
+      patterns:
+        - red
+        - type: blend
+          weight: 0.25
+          patterns:
+            - red
+            - type: solid
+              color: [0, 0, 1]
+").unwrap();
+
+        let object = scene.world.objects[0].as_ref();
+        let pattern = object.material().pattern.clone().unwrap();
+        assert_eq!(Color::new(1.0, 0.0, 0.0), pattern.pattern_at_shape(object, Tuple::point(0.5, 0.0, 0.0)));
+        assert_eq!(Color::new(0.75, 0.0, 0.25), pattern.pattern_at_shape(object, Tuple::point(1.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn parsing_a_texture_map_pattern() {
+        let scene = parse("
+- add: sphere
+  material:
+    pattern:
+      type: map
+      mapping: spherical
+      uv_pattern:
+        type: checkers
+        width: 16
+        height: 8
+        colors: [[0, 0, 0], [1, 1, 1]]
+").unwrap();
+
+        let object = scene.world.objects[0].as_ref();
+        let pattern = object.material().pattern.clone().unwrap();
+        assert_eq!(Color::new(1.0, 1.0, 1.0), pattern.pattern_at_shape(object, Tuple::point(0.4315, 0.4670, 0.7719)));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), pattern.pattern_at_shape(object, Tuple::point(-0.9654, 0.2552, -0.0534)));
+    }
+
+    #[test]
+    fn parsing_groups_and_csg() {
+        let scene = parse("
+- add: group
+  transform:
+    - [translate, 0, 0, 10]
+  children:
+    - add: sphere
+    - add: csg
+      operation: difference
+      left:
+        add: cube
+      right:
+        add: sphere
+        transform:
+          - [translate, 0, 0, -1]
+      transform:
+        - [translate, 5, 0, 0]
+").unwrap();
+        let r = Ray {
+            origin: Tuple::point(5.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = scene.world.intersect_world(&r);
+
+        // the sphere takes a bite out of the front of the cube
+        assert_eq!(2, xs.len());
+        assert_eq!(10.0, xs[0].t);
+        assert_eq!(11.0, xs[1].t);
+    }
+
+    #[test]
+    fn a_group_material_is_handed_to_children_without_one() {
+        let scene = parse("
+- add: group
+  material:
+    color: [1, 0, 0]
+  children:
+    - add: sphere
+").unwrap();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = scene.world.intersect_world(&r);
+
+        assert_eq!(Color::new(1.0, 0.0, 0.0), xs[0].object.material().color);
+    }
+
+    #[test]
+    fn a_child_material_wins_over_its_group_material() {
+        let scene = parse("
+- add: group
+  material:
+    color: [1, 0, 0]
+  children:
+    - add: sphere
+      material:
+        color: [0, 0, 1]
+").unwrap();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = scene.world.intersect_world(&r);
+
+        assert_eq!(Color::new(0.0, 0.0, 1.0), xs[0].object.material().color);
+    }
+
+    #[test]
+    fn a_csg_operand_keeps_its_own_material() {
+        let scene = parse("
+- add: csg
+  operation: union
+  material:
+    color: [1, 0, 0]
+  left:
+    add: cube
+    material:
+      color: [0, 0, 1]
+  right:
+    add: sphere
+    transform:
+      - [translate, 0, 0, 10]
+").unwrap();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = scene.world.intersect_world(&r);
+
+        assert_eq!(4, xs.len());
+        assert_eq!(Color::new(0.0, 0.0, 1.0), xs[0].object.material().color);
+        assert_eq!(Color::new(1.0, 0.0, 0.0), xs[2].object.material().color);
+    }
+
+    #[test]
+    fn loading_a_scene_with_a_mesh_next_to_it() {
+        let dir = std::env::temp_dir().join("loading_a_scene_with_a_mesh_next_to_it");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("triangle.obj"), "v 0 1 0\nv -1 0 0\nv 1 0 0\nf 1 2 3\n").unwrap();
+        std::fs::write(dir.join("scene.yaml"), CAMERA.to_string() + "- add: obj\n  file: triangle.obj\n  material:\n    color: [0, 1, 0]\n").unwrap();
+
+        let scene = Scene::from_file(dir.join("scene.yaml").to_str().unwrap()).unwrap();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.5, -2.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = scene.world.intersect_world(&r);
+        assert_eq!(1, xs.len());
+        assert_eq!(Color::new(0.0, 1.0, 0.0), xs[0].object.material().color);
+    }
+
+    #[test]
+    fn the_example_scene_loads() {
+        let scene = Scene::parse(include_str!("../scenes/spheres.yaml")).unwrap();
+
+        assert_eq!(1, scene.world.lights.len());
+        assert_eq!(2, scene.world.objects.len());
+    }
+
+    macro_rules! scene_error_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (entries, expected) = $value;
+
+                let actual = parse(entries);
+
+                assert_eq!(expected, actual.err().unwrap().to_string());
+            }
+        )*
+        }
+    }
+
+    scene_error_tests! {
+        an_unknown_shape: ("- add: teapot", "entry 2: (add: teapot) unknown definition 'teapot'"),
+        an_entry_without_add_or_define: ("- color: [1, 1, 1]", "entry 2: (unnamed entry) expected an 'add' or 'define' key"),
+        a_light_without_a_position: ("- add: light\n  intensity: [1, 1, 1]", "entry 2: (add: light) missing 'at'"),
+        a_color_that_is_not_a_triple: ("- add: light\n  at: [0, 0, 0]\n  intensity: [1, 1]", "entry 2: (add: light) 'intensity' must be a list of 3 numbers"),
+        an_unknown_material_property: ("- add: sphere\n  material:\n    colour: [1, 0, 0]", "entry 2: (add: sphere) material: unknown material property 'colour'"),
+        a_material_property_that_is_not_a_number: ("- add: sphere\n  material:\n    diffuse: lots", "entry 2: (add: sphere) material: 'diffuse' must be a number"),
+        an_unknown_transformation: ("- add: sphere\n  transform:\n    - [scale, 1, 1, 1]\n    - [spin, 1]", "entry 2: (add: sphere) transform: step 2: unknown transformation 'spin'"),
+        a_transformation_with_too_few_arguments: ("- add: sphere\n  transform:\n    - [translate, 1, 2]", "entry 2: (add: sphere) transform: step 1: 'translate' takes 3 numbers, found 2"),
+        an_unknown_material_definition: ("- add: sphere\n  material: shiny", "entry 2: (add: sphere) material: unknown definition 'shiny'"),
+        an_unknown_pattern_type: ("- add: plane\n  material:\n    pattern:\n      type: zigzag", "entry 2: (add: plane) material: pattern: unknown pattern type 'zigzag'"),
+        a_bad_group_child: ("- add: group\n  children:\n    - add: sphere\n    - add: cube\n      material:\n        ambient: x", "entry 2: (add: group) children[1]: material: 'ambient' must be a number"),
+        a_define_without_a_value: ("- define: nothing", "entry 2: (define: nothing) missing 'value'"),
+        an_unknown_csg_operation: ("- add: csg\n  operation: xor", "entry 2: (add: csg) unknown csg operation 'xor'"),
+        an_unknown_tone_mapping: ("- add: display\n  tone-mapping: filmic", "entry 2: (add: display) unknown tone mapping 'filmic'"),
+        an_unsupported_bit_depth: ("- add: display\n  bit-depth: 12", "entry 2: (add: display) 'bit-depth' must be 8 or 16"),
+        an_unknown_ppm_encoding: ("- add: display\n  ppm: ascii", "entry 2: (add: display) unknown ppm encoding 'ascii', expected plain or binary"),
+        an_extended_reinhard_without_white: ("- add: display\n  tone-mapping: extended-reinhard", "entry 2: (add: display) missing 'white'"),
+        a_transform_that_refers_to_itself: ("- define: t\n  value: [t]\n- add: sphere\n  transform: t", "entry 3: (add: sphere) transform: definition 't' refers to itself"),
+        a_shape_that_refers_to_itself: ("- define: ball\n  value:\n    add: ball\n- add: ball", "entry 3: (add: ball) definition 'ball' refers to itself"),
+        definitions_that_refer_to_each_other: ("- define: a\n  value: [b]\n- define: b\n  value: [a]\n- add: sphere\n  transform: a", "entry 4: (add: sphere) transform: definition 'a' refers to itself"),
+        a_material_that_refers_to_itself: ("- define: m\n  value: m\n- add: sphere\n  material: m", "entry 3: (add: sphere) material: definition 'm' refers to itself"),
+        a_pattern_that_refers_to_itself: ("- define: p\n  value:\n    type: perturb\n    pattern: p\n- add: sphere\n  material:\n    pattern: p", "entry 3: (add: sphere) material: pattern: pattern: definition 'p' refers to itself"),
+        a_missing_mesh_file: ("- add: obj\n  file: /no/such/mesh.obj", "entry 2: (add: obj) couldn't read /no/such/mesh.obj: No such file or directory (os error 2)"),
+    }
+
+    #[test]
+    fn a_scene_without_display_settings_clamps_and_encodes_srgb() {
+        let scene = parse("").unwrap();
+
+        assert_eq!(DisplayTransform::default(), scene.display);
+    }
+
+    #[test]
+    fn parsing_display_settings() {
+        let scene = parse("
+- add: display
+  exposure: -1.5
+  tone-mapping: extended-reinhard
+  white: 4
+  srgb: false
+  bit-depth: 16
+  ppm: binary
+").unwrap();
+
+        assert_eq!(DisplayTransform {
+            exposure: -1.5,
+            tone_mapping: ToneMapping::ExtendedReinhard { white: 4.0 },
+            srgb: false,
+            sixteen_bit: true,
+            binary_ppm: true,
+        }, scene.display);
+    }
+
+    #[test]
+    fn a_scene_needs_a_camera() {
+        let actual = Scene::parse("- add: light\n  at: [0, 0, 0]\n  intensity: [1, 1, 1]");
+
+        assert_eq!(SceneError { entry: 0, message: "the scene has no camera".to_string() }, actual.err().unwrap());
+    }
+
+    #[test]
+    fn a_scene_must_be_a_list() {
+        let actual = Scene::parse("add: camera");
+
+        assert_eq!("expected a list of entries", actual.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn invalid_yaml_is_reported() {
+        let actual = Scene::parse("- add: [camera");
+
+        assert!(actual.err().unwrap().to_string().starts_with("invalid YAML: "));
+    }
+}
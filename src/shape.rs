@@ -0,0 +1,237 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::matrix::Matrix4;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::tuple::Tuple;
+use crate::intersection::{Intersection, Intersections};
+
+pub fn next_shape_id() -> usize {
+    static COUNTER: AtomicUsize = AtomicUsize::new(1);
+    return COUNTER.fetch_add(1, Ordering::Relaxed);
+}
+
+pub trait Shape: Debug {
+    fn id(&self) -> usize;
+    fn transform(&self) -> Matrix4;
+    fn set_transform(&mut self, new_transform: Matrix4);
+    fn material(&self) -> &Material;
+    fn set_material(&mut self, new_material: Material);
+    // the combined transform of every group this shape is nested in
+    fn parent_transform(&self) -> Matrix4;
+    fn set_parent_transform(&mut self, new_parent_transform: Matrix4);
+
+    // intersects a ray that was already converted to object space
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_>;
+    // the normal at a point that was already converted to object space
+    fn local_normal_at(&self, local_point: Tuple) -> Tuple;
+
+    // shapes that interpolate their normal across the surface override this to use the hit's u/v
+    fn local_normal_at_hit(&self, local_point: Tuple, _hit: &Intersection) -> Tuple {
+        return self.local_normal_at(local_point);
+    }
+
+    // composite shapes also include everything they contain
+    fn includes(&self, other: &dyn Shape) -> bool {
+        return self.id() == other.id();
+    }
+
+    fn world_to_object(&self, world_point: Tuple) -> Tuple {
+        return (self.parent_transform() * self.transform()).inverse() * world_point;
+    }
+
+    fn normal_to_world(&self, object_normal: Tuple) -> Tuple {
+        let inverse = (self.parent_transform() * self.transform()).inverse();
+        let mut world_normal = inverse.transpose() * object_normal;
+        world_normal.w = 0.0;
+
+        return world_normal.normalize();
+    }
+
+    #[allow(dead_code)]
+    fn normal_at(&self, world_point: Tuple) -> Tuple {
+        let local_point = self.world_to_object(world_point);
+        let local_normal = self.local_normal_at(local_point);
+
+        return self.normal_to_world(local_normal);
+    }
+
+    fn normal_at_hit(&self, world_point: Tuple, hit: &Intersection) -> Tuple {
+        let local_point = self.world_to_object(world_point);
+        let local_normal = self.local_normal_at_hit(local_point, hit);
+
+        return self.normal_to_world(local_normal);
+    }
+}
+
+#[macro_export]
+macro_rules! shape_properties {
+    () => {
+        fn id(&self) -> usize {
+            return self.id;
+        }
+
+        fn transform(&self) -> $crate::matrix::Matrix4 {
+            return self.transform;
+        }
+
+        fn set_transform(&mut self, new_transform: $crate::matrix::Matrix4) {
+            self.transform = new_transform;
+        }
+
+        fn material(&self) -> &$crate::material::Material {
+            return &self.material;
+        }
+
+        fn set_material(&mut self, new_material: $crate::material::Material) {
+            self.material = new_material;
+        }
+
+        fn parent_transform(&self) -> $crate::matrix::Matrix4 {
+            return self.parent_transform;
+        }
+
+        fn set_parent_transform(&mut self, new_parent_transform: $crate::matrix::Matrix4) {
+            self.parent_transform = new_parent_transform;
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::RefCell;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+    use crate::shape::{Shape, next_shape_id};
+    use crate::matrix::Matrix4;
+    use crate::material::Material;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+    use crate::intersection::Intersections;
+
+    #[derive(Debug)]
+    pub struct TestShape {
+        id: usize,
+        transform: Matrix4,
+        material: Material,
+        parent_transform: Matrix4,
+        pub saved_ray: RefCell<Option<Ray>>,
+    }
+
+    impl TestShape {
+        pub fn new() -> Self {
+            return TestShape {
+                id: next_shape_id(),
+                transform: Matrix4::identity(),
+                material: Material::default(),
+                parent_transform: Matrix4::identity(),
+                saved_ray: RefCell::new(None),
+            };
+        }
+    }
+
+    impl Shape for TestShape {
+        shape_properties!();
+
+        fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+            self.saved_ray.replace(Some(*local_ray));
+            return Intersections::new();
+        }
+
+        fn local_normal_at(&self, local_point: Tuple) -> Tuple {
+            return Tuple::vector(local_point.x, local_point.y, local_point.z);
+        }
+    }
+
+    #[test]
+    fn the_default_transformation() {
+        let s = TestShape::new();
+
+        assert_eq!(Matrix4::identity(), s.transform());
+    }
+
+    #[test]
+    fn assigning_a_transformation() {
+        let mut s = TestShape::new();
+
+        s.set_transform(Matrix4::translation(2.0, 3.0, 4.0));
+
+        assert_eq!(Matrix4::translation(2.0, 3.0, 4.0), s.transform());
+    }
+
+    #[test]
+    fn the_default_material() {
+        let s = TestShape::new();
+
+        assert_eq!(&Material::default(), s.material());
+    }
+
+    #[test]
+    fn assigning_a_material() {
+        let mut s = TestShape::new();
+        let m = Material { ambient: 1.0, ..Material::default() };
+
+        s.set_material(m.clone());
+
+        assert_eq!(&m, s.material());
+    }
+
+    #[test]
+    fn every_shape_gets_a_unique_id() {
+        let s1 = TestShape::new();
+        let s2 = TestShape::new();
+
+        assert_ne!(s1.id(), s2.id());
+    }
+
+    #[test]
+    fn intersecting_a_scaled_shape_with_a_ray() {
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+
+        r.intersect(&s);
+
+        let saved_ray = s.saved_ray.borrow().unwrap();
+        assert_eq!(Tuple::point(0.0, 0.0, -2.5), saved_ray.origin);
+        assert_eq!(Tuple::vector(0.0, 0.0, 0.5), saved_ray.direction);
+    }
+
+    #[test]
+    fn intersecting_a_translated_shape_with_a_ray() {
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::translation(5.0, 0.0, 0.0));
+
+        r.intersect(&s);
+
+        let saved_ray = s.saved_ray.borrow().unwrap();
+        assert_eq!(Tuple::point(-5.0, 0.0, -5.0), saved_ray.origin);
+        assert_eq!(Tuple::vector(0.0, 0.0, 1.0), saved_ray.direction);
+    }
+
+    #[test]
+    fn computing_the_normal_on_a_translated_shape() {
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::translation(0.0, 1.0, 0.0));
+
+        let n = s.normal_at(Tuple::point(0.0, 1.70711, -FRAC_1_SQRT_2));
+
+        assert_eq!(Tuple::vector(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2).round(), n.round());
+    }
+
+    #[test]
+    fn computing_the_normal_on_a_transformed_shape() {
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::scaling(1.0, 0.5, 1.0) * Matrix4::rotation_z(PI / 5.0));
+
+        let n = s.normal_at(Tuple::point(0.0, 2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0));
+
+        assert_eq!(Tuple::vector(0.0, 0.97014, -0.24254), n.round());
+    }
+}
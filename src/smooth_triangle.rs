@@ -0,0 +1,138 @@
+use crate::matrix::Matrix4;
+use crate::tuple::Tuple;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+use crate::triangle::intersect_triangle;
+use crate::shape_properties;
+
+// a triangle with a normal per vertex, interpolated across its face at the hit's u/v
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct SmoothTriangle {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub n1: Tuple,
+    pub n2: Tuple,
+    pub n3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> Self {
+        return SmoothTriangle {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+        };
+    }
+}
+
+impl Shape for SmoothTriangle {
+    shape_properties!();
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        return match intersect_triangle(self.p1, self.e1, self.e2, local_ray) {
+            Some((t, u, v)) => Intersections { values: vec!(Intersection::with_uv(t, self, u, v)) },
+            None => Intersections::new(),
+        };
+    }
+
+    // without a hit there is nothing to interpolate, so fall back to the flat face normal
+    fn local_normal_at(&self, _local_point: Tuple) -> Tuple {
+        return self.e2.cross(self.e1).normalize();
+    }
+
+    fn local_normal_at_hit(&self, _local_point: Tuple, hit: &Intersection) -> Tuple {
+        return self.n2 * hit.u + self.n3 * hit.v + self.n1 * (1.0 - hit.u - hit.v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::smooth_triangle::SmoothTriangle;
+    use crate::shape::Shape;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+    use crate::intersection::{Intersection, Intersections};
+
+    fn test_smooth_triangle() -> SmoothTriangle {
+        return SmoothTriangle::new(
+            Tuple::point(0.0, 1.0, 0.0),
+            Tuple::point(-1.0, 0.0, 0.0),
+            Tuple::point(1.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+            Tuple::vector(1.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn constructing_a_smooth_triangle() {
+        let tri = test_smooth_triangle();
+
+        assert_eq!(Tuple::point(0.0, 1.0, 0.0), tri.p1);
+        assert_eq!(Tuple::point(-1.0, 0.0, 0.0), tri.p2);
+        assert_eq!(Tuple::point(1.0, 0.0, 0.0), tri.p3);
+        assert_eq!(Tuple::vector(0.0, 1.0, 0.0), tri.n1);
+        assert_eq!(Tuple::vector(-1.0, 0.0, 0.0), tri.n2);
+        assert_eq!(Tuple::vector(1.0, 0.0, 0.0), tri.n3);
+    }
+
+    #[test]
+    fn an_intersection_with_a_smooth_triangle_stores_u_and_v() {
+        let tri = test_smooth_triangle();
+        let r = Ray {
+            origin: Tuple::point(-0.2, 0.3, -2.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = tri.local_intersect(&r);
+
+        assert_eq!(1, xs.len());
+        assert_eq!(0.45, (xs[0].u * 100000.0).round() / 100000.0);
+        assert_eq!(0.25, (xs[0].v * 100000.0).round() / 100000.0);
+    }
+
+    #[test]
+    fn a_smooth_triangle_uses_u_and_v_to_interpolate_the_normal() {
+        let tri = test_smooth_triangle();
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+
+        let n = tri.normal_at_hit(Tuple::point(0.0, 0.0, 0.0), &i);
+
+        assert_eq!(Tuple::vector(-0.5547, 0.83205, 0.0), n.round());
+    }
+
+    #[test]
+    fn preparing_the_normal_on_a_smooth_triangle() {
+        let tri = test_smooth_triangle();
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let r = Ray {
+            origin: Tuple::point(-0.2, 0.3, -2.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let xs = Intersections { values: vec!(i) };
+
+        let comps = xs[0].prepare_computations(&r, &xs);
+
+        assert_eq!(Tuple::vector(-0.5547, 0.83205, 0.0), comps.normalv.round());
+    }
+}
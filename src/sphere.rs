@@ -1,49 +1,74 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::matrix::Matrix4;
 use crate::tuple::Tuple;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+use crate::shape_properties;
 
 #[derive(Debug)]
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 #[derive(PartialEq)]
 pub struct Sphere {
     pub id: usize,
     pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
 }
 
 impl Sphere {
     pub fn new() -> Self {
-        static COUNTER: AtomicUsize = AtomicUsize::new(1);
-        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
-
         return Sphere {
-            id,
+            id: next_shape_id(),
             transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
         };
     }
+}
 
-    pub fn set_transform(&mut self, new_transform: Matrix4) {
-        self.transform = new_transform;
-    }
+impl Shape for Sphere {
+    shape_properties!();
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        let sphere_to_ray = local_ray.origin - Tuple::point(0.0, 0.0, 0.0);
 
-    #[allow(dead_code)]
-    pub fn normal_at(&self, world_point: Tuple) -> Tuple {
-        let object_point = self.transform.inverse() * world_point;
-        let object_normal = object_point - Tuple::point(0.0,0.0,0.0);
-        let mut world_normal = self.transform.inverse().transpose() * object_normal;
-        world_normal.w = 0.0;
+        let a = local_ray.direction.dot(local_ray.direction);
+        let b = 2.0 * local_ray.direction.dot(sphere_to_ray);
+        let c = sphere_to_ray.dot(sphere_to_ray) - 1.0;
 
-        return world_normal.normalize();
+        let discriminate = (b * b) - (4.0 * a * c);
+
+        if discriminate < 0.0 {
+            return Intersections::new();
+        }
+
+        let t1 = (-b - discriminate.sqrt()) / (2.0 * a);
+        let t2 = (-b + discriminate.sqrt()) / (2.0 * a);
+
+        return Intersections {
+            values: vec!(
+                Intersection::new(t1, self),
+                Intersection::new(t2, self)
+            )
+        };
+    }
+
+    fn local_normal_at(&self, local_point: Tuple) -> Tuple {
+        return local_point - Tuple::point(0.0, 0.0, 0.0);
     }
 }
 
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use std::f64::consts::{FRAC_1_SQRT_2, PI};
     use crate::sphere::{Sphere};
     use crate::matrix::Matrix4;
     use crate::ray::Ray;
     use crate::tuple::Tuple;
+    use crate::material::Material;
+    use crate::shape::Shape;
 
     #[test]
     fn a_sphere_default_transformation() {
@@ -70,7 +95,7 @@ mod tests {
         };
         let mut s = Sphere::new();
         s.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
-        let xs = r.intersect(s);
+        let xs = r.intersect(&s);
 
         assert_eq!(2, xs.len());
         assert_eq!(3.0, xs[0].t);
@@ -115,5 +140,42 @@ mod tests {
 
         assert_eq!(Tuple::vector(1.0, 0.0, 0.0), r.round());
     }
-}
 
+    #[test]
+    fn a_sphere_has_a_default_material() {
+        let s = Sphere::new();
+
+        assert_eq!(Material::default(), s.material);
+    }
+
+    #[test]
+    fn a_sphere_may_be_assigned_a_material() {
+        let mut s = Sphere::new();
+        let m = Material { ambient: 1.0, ..Material::default() };
+
+        s.set_material(m.clone());
+
+        assert_eq!(m, s.material);
+    }
+
+    // a unit sphere of solid glass
+    pub fn glass_sphere() -> Sphere {
+        let mut s = Sphere::new();
+        s.set_material(Material {
+            transparency: 1.0,
+            refractive_index: 1.5,
+            ..Material::default()
+        });
+
+        return s;
+    }
+
+    #[test]
+    fn a_helper_for_producing_a_sphere_with_a_glassy_material() {
+        let s = glass_sphere();
+
+        assert_eq!(Matrix4::identity(), s.transform());
+        assert_eq!(1.0, s.material().transparency);
+        assert_eq!(1.5, s.material().refractive_index);
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt::Debug;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::matrix::Matrix4;
+use crate::pattern::Pattern;
+use crate::pattern_properties;
+use crate::tuple::Tuple;
+
+// a 2D pattern looked up by (u, v), both in 0..1
+pub trait UvPattern: Debug {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color;
+}
+
+// a checkerboard of width x height squares, mostly useful to check how a mapping lays out
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct UvCheckers {
+    pub width: f64,
+    pub height: f64,
+    pub a: Color,
+    pub b: Color,
+}
+
+impl UvCheckers {
+    pub fn new(width: f64, height: f64, a: Color, b: Color) -> Self {
+        return UvCheckers { width, height, a, b };
+    }
+}
+
+impl UvPattern for UvCheckers {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let u2 = (u * self.width).floor();
+        let v2 = (v * self.height).floor();
+
+        if (u2 + v2).rem_euclid(2.0) == 0.0 {
+            return self.a;
+        }
+
+        return self.b;
+    }
+}
+
+// samples an image, v = 0 is the bottom row of the canvas
+#[derive(Debug)]
+pub struct UvImage {
+    pub canvas: Canvas,
+}
+
+impl UvImage {
+    // sampling needs at least one pixel to land on
+    pub fn new(canvas: Canvas) -> Result<Self, String> {
+        if canvas.width() == 0 || canvas.height() == 0 {
+            return Err(format!("an image texture needs at least one pixel, found a {}x{} image",
+                               canvas.width(), canvas.height()));
+        }
+
+        return Ok(UvImage { canvas });
+    }
+
+    pub fn from_file(file_name: &str) -> Result<Self, String> {
+        return UvImage::new(Canvas::from_file(file_name)?)
+            .map_err(|why| format!("couldn't load {}: {}", file_name, why));
+    }
+}
+
+impl UvPattern for UvImage {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let v = 1.0 - v;
+
+        let x = (u * (self.canvas.width() - 1) as f64).round() as usize;
+        let y = (v * (self.canvas.height() - 1) as f64).round() as usize;
+
+        return self.canvas.pixel_at(x, y);
+    }
+}
+
+// wraps around a unit sphere, u runs once around the equator and v from the south to the north pole
+pub fn spherical_map(point: Tuple) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let radius = Tuple::vector(point.x, point.y, point.z).magnitude();
+    let phi = (point.y / radius).acos();
+
+    let raw_u = theta / (2.0 * PI);
+    let u = 1.0 - (raw_u + 0.5);
+    let v = 1.0 - phi / PI;
+
+    return (u, v);
+}
+
+// tiles the xz plane, repeating every unit
+pub fn planar_map(point: Tuple) -> (f64, f64) {
+    return (point.x.rem_euclid(1.0), point.z.rem_euclid(1.0));
+}
+
+// wraps around the y axis once, repeating every unit in y
+pub fn cylindrical_map(point: Tuple) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let raw_u = theta / (2.0 * PI);
+    let u = 1.0 - (raw_u + 0.5);
+    let v = point.y.rem_euclid(1.0);
+
+    return (u, v);
+}
+
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+// the face of the unit cube a point lies on, picked by its largest coordinate
+pub fn face_from_point(point: Tuple) -> CubeFace {
+    let coord = point.x.abs().max(point.y.abs()).max(point.z.abs());
+
+    if coord == point.x {
+        return CubeFace::Right;
+    }
+    if coord == -point.x {
+        return CubeFace::Left;
+    }
+    if coord == point.y {
+        return CubeFace::Up;
+    }
+    if coord == -point.y {
+        return CubeFace::Down;
+    }
+    if coord == point.z {
+        return CubeFace::Front;
+    }
+
+    return CubeFace::Back;
+}
+
+// the (u, v) of a point within its own face of the unit cube
+pub fn cube_face_uv(face: CubeFace, point: Tuple) -> (f64, f64) {
+    let (u, v) = match face {
+        CubeFace::Front => (point.x + 1.0, point.y + 1.0),
+        CubeFace::Back => (1.0 - point.x, point.y + 1.0),
+        CubeFace::Left => (point.z + 1.0, point.y + 1.0),
+        CubeFace::Right => (1.0 - point.z, point.y + 1.0),
+        CubeFace::Up => (point.x + 1.0, 1.0 - point.z),
+        CubeFace::Down => (point.x + 1.0, point.z + 1.0),
+    };
+
+    return (u.rem_euclid(2.0) / 2.0, v.rem_euclid(2.0) / 2.0);
+}
+
+// lays the six faces out as a horizontal cross, the usual skybox image layout:
+//         up
+//   left front right back
+//        down
+pub fn cube_map(point: Tuple) -> (f64, f64) {
+    let face = face_from_point(point);
+    let (u, v) = cube_face_uv(face, point);
+
+    let (column, row) = match face {
+        CubeFace::Left => (0.0, 1.0),
+        CubeFace::Front => (1.0, 1.0),
+        CubeFace::Right => (2.0, 1.0),
+        CubeFace::Back => (3.0, 1.0),
+        CubeFace::Up => (1.0, 2.0),
+        CubeFace::Down => (1.0, 0.0),
+    };
+
+    return ((column + u) / 4.0, (row + v) / 3.0);
+}
+
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub enum UvMapping {
+    Spherical,
+    Planar,
+    Cylindrical,
+    Cube,
+}
+
+impl UvMapping {
+    pub fn map(&self, point: Tuple) -> (f64, f64) {
+        return match self {
+            UvMapping::Spherical => spherical_map(point),
+            UvMapping::Planar => planar_map(point),
+            UvMapping::Cylindrical => cylindrical_map(point),
+            UvMapping::Cube => cube_map(point),
+        };
+    }
+}
+
+// projects a 2D pattern, e.g. an image, onto a shape through a mapping
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct TextureMapPattern {
+    pub uv_pattern: Rc<dyn UvPattern>,
+    pub mapping: UvMapping,
+    pub(crate) transform: Matrix4,
+}
+
+impl TextureMapPattern {
+    pub fn new(uv_pattern: Rc<dyn UvPattern>, mapping: UvMapping) -> Self {
+        return TextureMapPattern { uv_pattern, mapping, transform: Matrix4::identity() };
+    }
+}
+
+impl Pattern for TextureMapPattern {
+    pattern_properties!();
+
+    fn pattern_at(&self, pattern_point: Tuple) -> Color {
+        let (u, v) = self.mapping.map(pattern_point);
+
+        return self.uv_pattern.uv_pattern_at(u, v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::f64::consts::FRAC_1_SQRT_2;
+    use crate::texture::{UvPattern, UvCheckers, UvImage, UvMapping, TextureMapPattern, CubeFace,
+                         spherical_map, planar_map, cylindrical_map, face_from_point, cube_face_uv, cube_map};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+    use crate::pattern::Pattern;
+    use crate::tuple::Tuple;
+
+    const WHITE: Color = Color { red: 1.0, green: 1.0, blue: 1.0 };
+    const BLACK: Color = Color { red: 0.0, green: 0.0, blue: 0.0 };
+
+    macro_rules! uv_checkers_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (u, v, expected) = $value;
+                let checkers = UvCheckers::new(2.0, 2.0, BLACK, WHITE);
+
+                let actual = checkers.uv_pattern_at(u, v);
+
+                assert_eq!(expected, actual);
+            }
+        )*
+        }
+    }
+
+    uv_checkers_tests! {
+        checker_pattern_in_2d_0_0: (0.0, 0.0, BLACK),
+        checker_pattern_in_2d_0_5_0: (0.5, 0.0, WHITE),
+        checker_pattern_in_2d_0_0_5: (0.0, 0.5, WHITE),
+        checker_pattern_in_2d_0_5_0_5: (0.5, 0.5, BLACK),
+        checker_pattern_in_2d_1_1: (1.0, 1.0, BLACK),
+    }
+
+    macro_rules! uv_mapping_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (map, point, expected): (fn(Tuple) -> (f64, f64), Tuple, (f64, f64)) = $value;
+
+                let (u, v) = map(point);
+
+                assert_eq!(expected, ((u * 100000.0).round() / 100000.0, (v * 100000.0).round() / 100000.0));
+            }
+        )*
+        }
+    }
+
+    uv_mapping_tests! {
+        spherical_mapping_front: (spherical_map, Tuple::point(0.0, 0.0, -1.0), (0.0, 0.5)),
+        spherical_mapping_right: (spherical_map, Tuple::point(1.0, 0.0, 0.0), (0.25, 0.5)),
+        spherical_mapping_back: (spherical_map, Tuple::point(0.0, 0.0, 1.0), (0.5, 0.5)),
+        spherical_mapping_left: (spherical_map, Tuple::point(-1.0, 0.0, 0.0), (0.75, 0.5)),
+        spherical_mapping_north_pole: (spherical_map, Tuple::point(0.0, 1.0, 0.0), (0.5, 1.0)),
+        spherical_mapping_south_pole: (spherical_map, Tuple::point(0.0, -1.0, 0.0), (0.5, 0.0)),
+        spherical_mapping_diagonal: (spherical_map, Tuple::point(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0), (0.25, 0.75)),
+        planar_mapping_0: (planar_map, Tuple::point(0.25, 0.0, 0.5), (0.25, 0.5)),
+        planar_mapping_1: (planar_map, Tuple::point(0.25, 0.0, -0.25), (0.25, 0.75)),
+        planar_mapping_2: (planar_map, Tuple::point(0.25, 0.5, -0.25), (0.25, 0.75)),
+        planar_mapping_3: (planar_map, Tuple::point(1.25, 0.0, 0.5), (0.25, 0.5)),
+        planar_mapping_4: (planar_map, Tuple::point(0.25, 0.0, -1.75), (0.25, 0.25)),
+        planar_mapping_5: (planar_map, Tuple::point(1.0, 0.0, -1.0), (0.0, 0.0)),
+        planar_mapping_6: (planar_map, Tuple::point(0.0, 0.0, 0.0), (0.0, 0.0)),
+        cylindrical_mapping_0: (cylindrical_map, Tuple::point(0.0, 0.0, -1.0), (0.0, 0.0)),
+        cylindrical_mapping_1: (cylindrical_map, Tuple::point(0.0, 0.5, -1.0), (0.0, 0.5)),
+        cylindrical_mapping_2: (cylindrical_map, Tuple::point(0.0, 1.0, -1.0), (0.0, 0.0)),
+        cylindrical_mapping_3: (cylindrical_map, Tuple::point(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.125, 0.5)),
+        cylindrical_mapping_4: (cylindrical_map, Tuple::point(1.0, 0.5, 0.0), (0.25, 0.5)),
+        cylindrical_mapping_5: (cylindrical_map, Tuple::point(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.375, 0.5)),
+        cylindrical_mapping_6: (cylindrical_map, Tuple::point(0.0, -0.25, 1.0), (0.5, 0.75)),
+        cylindrical_mapping_7: (cylindrical_map, Tuple::point(-FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.625, 0.5)),
+        cylindrical_mapping_8: (cylindrical_map, Tuple::point(-1.0, 1.25, 0.0), (0.75, 0.25)),
+        cylindrical_mapping_9: (cylindrical_map, Tuple::point(-FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.875, 0.5)),
+    }
+
+    macro_rules! face_from_point_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, expected) = $value;
+
+                assert_eq!(expected, face_from_point(point));
+            }
+        )*
+        }
+    }
+
+    face_from_point_tests! {
+        identifying_the_face_left: (Tuple::point(-1.0, 0.5, -0.25), CubeFace::Left),
+        identifying_the_face_right: (Tuple::point(1.1, -0.75, 0.8), CubeFace::Right),
+        identifying_the_face_front: (Tuple::point(0.1, 0.6, 0.9), CubeFace::Front),
+        identifying_the_face_back: (Tuple::point(-0.7, 0.0, -2.0), CubeFace::Back),
+        identifying_the_face_up: (Tuple::point(0.5, 1.0, 0.9), CubeFace::Up),
+        identifying_the_face_down: (Tuple::point(-0.2, -1.3, 1.1), CubeFace::Down),
+    }
+
+    macro_rules! cube_face_uv_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (face, point, expected) = $value;
+
+                assert_eq!(expected, cube_face_uv(face, point));
+            }
+        )*
+        }
+    }
+
+    cube_face_uv_tests! {
+        uv_mapping_the_front_face_0: (CubeFace::Front, Tuple::point(-0.5, 0.5, 1.0), (0.25, 0.75)),
+        uv_mapping_the_front_face_1: (CubeFace::Front, Tuple::point(0.5, -0.5, 1.0), (0.75, 0.25)),
+        uv_mapping_the_back_face_0: (CubeFace::Back, Tuple::point(0.5, 0.5, -1.0), (0.25, 0.75)),
+        uv_mapping_the_back_face_1: (CubeFace::Back, Tuple::point(-0.5, -0.5, -1.0), (0.75, 0.25)),
+        uv_mapping_the_left_face_0: (CubeFace::Left, Tuple::point(-1.0, 0.5, -0.5), (0.25, 0.75)),
+        uv_mapping_the_left_face_1: (CubeFace::Left, Tuple::point(-1.0, -0.5, 0.5), (0.75, 0.25)),
+        uv_mapping_the_right_face_0: (CubeFace::Right, Tuple::point(1.0, 0.5, 0.5), (0.25, 0.75)),
+        uv_mapping_the_right_face_1: (CubeFace::Right, Tuple::point(1.0, -0.5, -0.5), (0.75, 0.25)),
+        uv_mapping_the_upper_face_0: (CubeFace::Up, Tuple::point(-0.5, 1.0, -0.5), (0.25, 0.75)),
+        uv_mapping_the_upper_face_1: (CubeFace::Up, Tuple::point(0.5, 1.0, 0.5), (0.75, 0.25)),
+        uv_mapping_the_lower_face_0: (CubeFace::Down, Tuple::point(-0.5, -1.0, 0.5), (0.25, 0.75)),
+        uv_mapping_the_lower_face_1: (CubeFace::Down, Tuple::point(0.5, -1.0, -0.5), (0.75, 0.25)),
+    }
+
+    #[test]
+    fn cube_mapping_places_each_face_in_the_cross_layout() {
+        assert_eq!((0.125, 0.5), cube_map(Tuple::point(-1.0, 0.0, 0.0)));
+        assert_eq!((0.375, 0.5), cube_map(Tuple::point(0.0, 0.0, 1.0)));
+        assert_eq!((0.625, 0.5), cube_map(Tuple::point(1.0, 0.0, 0.0)));
+        assert_eq!((0.875, 0.5), cube_map(Tuple::point(0.0, 0.0, -1.0)));
+        assert_eq!((0.375, 2.5 / 3.0), cube_map(Tuple::point(0.0, 1.0, 0.0)));
+        assert_eq!((0.375, 0.5 / 3.0), cube_map(Tuple::point(0.0, -1.0, 0.0)));
+    }
+
+    macro_rules! texture_map_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, expected) = $value;
+                let checkers = UvCheckers::new(16.0, 8.0, BLACK, WHITE);
+                let pattern = TextureMapPattern::new(Rc::new(checkers), UvMapping::Spherical);
+
+                assert_eq!(expected, pattern.pattern_at(point));
+            }
+        )*
+        }
+    }
+
+    texture_map_tests! {
+        using_a_texture_map_pattern_with_a_spherical_map_0: (Tuple::point(0.4315, 0.4670, 0.7719), WHITE),
+        using_a_texture_map_pattern_with_a_spherical_map_1: (Tuple::point(-0.9654, 0.2552, -0.0534), BLACK),
+        using_a_texture_map_pattern_with_a_spherical_map_2: (Tuple::point(0.1039, 0.7090, 0.6975), WHITE),
+        using_a_texture_map_pattern_with_a_spherical_map_3: (Tuple::point(-0.4986, -0.7856, -0.3663), BLACK),
+        using_a_texture_map_pattern_with_a_spherical_map_4: (Tuple::point(-0.0317, -0.9395, 0.3411), BLACK),
+        using_a_texture_map_pattern_with_a_spherical_map_5: (Tuple::point(0.4809, -0.7721, 0.4154), BLACK),
+        using_a_texture_map_pattern_with_a_spherical_map_6: (Tuple::point(0.0285, -0.9612, -0.2745), BLACK),
+        using_a_texture_map_pattern_with_a_spherical_map_7: (Tuple::point(-0.5734, -0.2162, -0.7903), WHITE),
+        using_a_texture_map_pattern_with_a_spherical_map_8: (Tuple::point(0.7688, -0.1470, 0.6223), BLACK),
+        using_a_texture_map_pattern_with_a_spherical_map_9: (Tuple::point(-0.7652, 0.2175, 0.6060), BLACK),
+    }
+
+    fn gradient_image() -> UvImage {
+        let ppm = "P3\n10 10\n10\n".to_string() +
+            &(0..10)
+                .map(|row| (0..10).map(|col| {
+                    let value = ((row + col) % 10).to_string();
+                    format!("{} {} {}", value, value, value)
+                }).collect::<Vec<String>>().join("  "))
+                .collect::<Vec<String>>().join("\n");
+
+        return UvImage::new(Canvas::from_ppm(ppm.as_bytes()).unwrap()).unwrap();
+    }
+
+    macro_rules! uv_image_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (u, v, expected) = $value;
+                let pattern = gradient_image();
+
+                assert_eq!(expected, pattern.uv_pattern_at(u, v).round());
+            }
+        )*
+        }
+    }
+
+    uv_image_tests! {
+        checker_pattern_in_2d_image_0: (0.0, 0.0, Color::new(0.9, 0.9, 0.9)),
+        checker_pattern_in_2d_image_1: (0.3, 0.0, Color::new(0.2, 0.2, 0.2)),
+        checker_pattern_in_2d_image_2: (0.6, 0.3, Color::new(0.1, 0.1, 0.1)),
+        checker_pattern_in_2d_image_3: (1.0, 1.0, Color::new(0.9, 0.9, 0.9)),
+    }
+
+    #[test]
+    fn texture_mapping_an_image_from_a_file() {
+        let file_name = std::env::temp_dir().join("texture_mapping_an_image_from_a_file.ppm");
+        std::fs::write(&file_name, "P3\n2 1\n255\n255 0 0  0 0 255\n").unwrap();
+
+        let image = UvImage::from_file(file_name.to_str().unwrap()).unwrap();
+        let pattern = TextureMapPattern::new(Rc::new(image), UvMapping::Planar);
+
+        assert_eq!(Color::new(1.0, 0.0, 0.0), pattern.pattern_at(Tuple::point(0.25, 0.0, 0.5)));
+        assert_eq!(Color::new(0.0, 0.0, 1.0), pattern.pattern_at(Tuple::point(0.75, 0.0, 0.5)));
+    }
+
+    #[test]
+    fn loading_a_missing_image_file_fails() {
+        assert!(UvImage::from_file("/no/such/texture.ppm").is_err());
+    }
+
+    #[test]
+    fn an_empty_image_cannot_be_a_texture() {
+        let file_name = std::env::temp_dir().join("an_empty_image_cannot_be_a_texture.ppm");
+        std::fs::write(&file_name, "P3\n0 0\n255\n").unwrap();
+        let file_name = file_name.to_str().unwrap();
+
+        let result = UvImage::from_file(file_name);
+
+        assert_eq!(format!("couldn't load {}: an image texture needs at least one pixel, found a 0x0 image", file_name),
+                   result.unwrap_err());
+        assert!(UvImage::new(Canvas::new(3, 0)).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::matrix::Matrix4;
+use crate::tuple::{Tuple, EPSILON};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections};
+use crate::shape::{Shape, next_shape_id};
+use crate::shape_properties;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Triangle {
+    pub id: usize,
+    pub(crate) transform: Matrix4,
+    pub(crate) material: Material,
+    pub(crate) parent_transform: Matrix4,
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+    pub normal: Tuple,
+}
+
+impl Triangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        return Triangle {
+            id: next_shape_id(),
+            transform: Matrix4::identity(),
+            material: Material::default(),
+            parent_transform: Matrix4::identity(),
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(e1).normalize(),
+        };
+    }
+}
+
+// Möller–Trumbore: returns t and the barycentric u/v of the hit, if any
+pub fn intersect_triangle(p1: Tuple, e1: Tuple, e2: Tuple, ray: &Ray) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = ray.direction.cross(e2);
+    let det = e1.dot(dir_cross_e2);
+
+    // the ray is parallel to the triangle's plane
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = ray.origin - p1;
+    let u = f * p1_to_origin.dot(dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(e1);
+    let v = f * ray.direction.dot(origin_cross_e1);
+    if v < 0.0 || (u + v) > 1.0 {
+        return None;
+    }
+
+    let t = f * e2.dot(origin_cross_e1);
+
+    return Some((t, u, v));
+}
+
+impl Shape for Triangle {
+    shape_properties!();
+
+    fn local_intersect(&self, local_ray: &Ray) -> Intersections<'_> {
+        return match intersect_triangle(self.p1, self.e1, self.e2, local_ray) {
+            Some((t, u, v)) => Intersections { values: vec!(Intersection::with_uv(t, self, u, v)) },
+            None => Intersections::new(),
+        };
+    }
+
+    fn local_normal_at(&self, _local_point: Tuple) -> Tuple {
+        return self.normal;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::triangle::Triangle;
+    use crate::shape::Shape;
+    use crate::ray::Ray;
+    use crate::tuple::Tuple;
+
+    fn test_triangle() -> Triangle {
+        return Triangle::new(
+            Tuple::point(0.0, 1.0, 0.0),
+            Tuple::point(-1.0, 0.0, 0.0),
+            Tuple::point(1.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn constructing_a_triangle() {
+        let p1 = Tuple::point(0.0, 1.0, 0.0);
+        let p2 = Tuple::point(-1.0, 0.0, 0.0);
+        let p3 = Tuple::point(1.0, 0.0, 0.0);
+
+        let t = Triangle::new(p1, p2, p3);
+
+        assert_eq!(p1, t.p1);
+        assert_eq!(p2, t.p2);
+        assert_eq!(p3, t.p3);
+        assert_eq!(Tuple::vector(-1.0, -1.0, 0.0), t.e1);
+        assert_eq!(Tuple::vector(1.0, -1.0, 0.0), t.e2);
+        assert_eq!(Tuple::vector(0.0, 0.0, -1.0), t.normal);
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_triangle() {
+        let t = test_triangle();
+
+        let n1 = t.local_normal_at(Tuple::point(0.0, 0.5, 0.0));
+        let n2 = t.local_normal_at(Tuple::point(-0.5, 0.75, 0.0));
+        let n3 = t.local_normal_at(Tuple::point(0.5, 0.25, 0.0));
+
+        assert_eq!(t.normal, n1);
+        assert_eq!(t.normal, n2);
+        assert_eq!(t.normal, n3);
+    }
+
+    macro_rules! a_ray_misses_a_triangle_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (origin, direction) = $value;
+
+                let t = test_triangle();
+                let r = Ray { origin, direction };
+
+                let xs = t.local_intersect(&r);
+
+                assert_eq!(0, xs.len());
+            }
+        )*
+        }
+    }
+
+    a_ray_misses_a_triangle_tests! {
+        intersecting_a_ray_parallel_to_the_triangle: (Tuple::point(0.0, -1.0, -2.0), Tuple::vector(0.0, 1.0, 0.0)),
+        a_ray_misses_the_p1_p3_edge: (Tuple::point(1.0, 1.0, -2.0), Tuple::vector(0.0, 0.0, 1.0)),
+        a_ray_misses_the_p1_p2_edge: (Tuple::point(-1.0, 1.0, -2.0), Tuple::vector(0.0, 0.0, 1.0)),
+        a_ray_misses_the_p2_p3_edge: (Tuple::point(0.0, -1.0, -2.0), Tuple::vector(0.0, 0.0, 1.0)),
+    }
+
+    #[test]
+    fn a_ray_strikes_a_triangle() {
+        let t = test_triangle();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.5, -2.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = t.local_intersect(&r);
+
+        assert_eq!(1, xs.len());
+        assert_eq!(2.0, xs[0].t);
+        assert_eq!(t.id, xs[0].object.id());
+    }
+}
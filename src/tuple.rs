@@ -1,6 +1,8 @@
 use core::ops;
 use std::ops::{Index, IndexMut};
 
+pub const EPSILON: f64 = 0.00001;
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Tuple {
     pub(crate) x: f64,
@@ -65,7 +67,6 @@ impl Tuple {
         };
     }
 
-    #[allow(dead_code)]
     pub fn reflect(self, normal: Tuple) -> Tuple {
         return self - normal * 2.0 * self.dot(normal);
     }
@@ -174,7 +175,7 @@ mod tests {
             w: 1.0,
         };
 
-        assert_eq!(true, target.is_point());
+        assert!(target.is_point());
     }
 
     #[test]
@@ -186,7 +187,7 @@ mod tests {
             w: 1.0,
         };
 
-        assert_eq!(false, target.is_vector());
+        assert!(!target.is_vector());
     }
 
     #[test]
@@ -198,7 +199,7 @@ mod tests {
             w: 0.0,
         };
 
-        assert_eq!(false, target.is_point());
+        assert!(!target.is_point());
     }
 
     #[test]
@@ -210,7 +211,7 @@ mod tests {
             w: 0.0,
         };
 
-        assert_eq!(true, target.is_vector());
+        assert!(target.is_vector());
     }
 
     #[test]
@@ -0,0 +1,641 @@
+use crate::sphere::Sphere;
+use crate::shape::Shape;
+use crate::light::{PointLight, lighting};
+use crate::ray::Ray;
+use crate::intersection::{Intersection, Intersections, Computations};
+use crate::color::Color;
+use crate::tuple::Tuple;
+use crate::material::Material;
+use crate::matrix::Matrix4;
+
+#[derive(Debug)]
+pub struct World {
+    pub objects: Vec<Box<dyn Shape>>,
+    pub lights: Vec<PointLight>,
+    // how many times a ray may bounce, so facing mirrors cannot recurse forever
+    pub max_depth: usize,
+}
+
+impl World {
+    pub fn new() -> Self {
+        return World {
+            objects: Vec::new(),
+            lights: Vec::new(),
+            max_depth: 5,
+        };
+    }
+
+    pub fn intersect_world(&self, ray: &Ray) -> Intersections<'_> {
+        let mut values: Vec<Intersection> = self.objects.iter()
+            .flat_map(|object| ray.intersect(object.as_ref()).values)
+            .collect();
+
+        values.sort_by(|x, y| x.t.partial_cmp(&y.t).unwrap());
+
+        return Intersections { values };
+    }
+
+    // remaining is how many more bounces the ray is allowed
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
+        let surface = self.lights.iter()
+            .fold(Color::default(), |color, light| {
+                let in_shadow = self.is_shadowed(comps.over_point, light);
+                color + lighting(comps.object.material(), comps.object, light, comps.over_point, comps.eyev,
+                                 comps.normalv, in_shadow)
+            });
+
+        let reflected = self.reflected_color(comps, remaining);
+        let refracted = self.refracted_color(comps, remaining);
+
+        let material = comps.object.material();
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            return surface + reflected * reflectance + refracted * (1.0 - reflectance);
+        }
+
+        return surface + reflected + refracted;
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let reflective = comps.object.material().reflective;
+        if remaining == 0 || reflective == 0.0 {
+            return Color::default();
+        }
+
+        let reflect_ray = Ray {
+            origin: comps.over_point,
+            direction: comps.reflectv,
+        };
+
+        return self.color_at_depth(&reflect_ray, remaining - 1) * reflective;
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let transparency = comps.object.material().transparency;
+        if remaining == 0 || transparency == 0.0 {
+            return Color::default();
+        }
+
+        // Snell's law, sin(theta_t) from sin(theta_i)
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(comps.normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            // total internal reflection
+            return Color::default();
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let refract_ray = Ray {
+            origin: comps.under_point,
+            direction: comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio,
+        };
+
+        return self.color_at_depth(&refract_ray, remaining - 1) * transparency;
+    }
+
+    pub fn is_shadowed(&self, point: Tuple, light: &PointLight) -> bool {
+        let v = light.position - point;
+        let distance = v.magnitude();
+        let ray = Ray {
+            origin: point,
+            direction: v.normalize(),
+        };
+
+        return match self.intersect_world(&ray).hit() {
+            Some(hit) => hit.t < distance,
+            None => false,
+        };
+    }
+
+    pub fn color_at(&self, ray: &Ray) -> Color {
+        return self.color_at_depth(ray, self.max_depth);
+    }
+
+    pub fn color_at_depth(&self, ray: &Ray, remaining: usize) -> Color {
+        let xs = self.intersect_world(ray);
+
+        return match xs.hit() {
+            Some(hit) => self.shade_hit(&hit.prepare_computations(ray, &xs), remaining),
+            None => Color::default(),
+        };
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        let light = PointLight {
+            position: Tuple::point(-10.0, 10.0, -10.0),
+            intensity: Color::new(1.0, 1.0, 1.0),
+        };
+
+        let mut s1 = Sphere::new();
+        s1.set_material(Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            diffuse: 0.7,
+            specular: 0.2,
+            ..Material::default()
+        });
+
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::scaling(0.5, 0.5, 0.5));
+
+        return World {
+            objects: vec!(Box::new(s1), Box::new(s2)),
+            lights: vec!(light),
+            max_depth: 5,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::light::PointLight;
+    use crate::color::Color;
+    use crate::tuple::Tuple;
+    use crate::sphere::Sphere;
+    use crate::material::Material;
+    use crate::matrix::Matrix4;
+    use crate::ray::Ray;
+    use crate::intersection::{Intersection, Intersections};
+    use crate::shape::Shape;
+    use crate::plane::Plane;
+    use crate::pattern::tests::TestPattern;
+    use std::rc::Rc;
+    use std::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+    #[test]
+    fn creating_a_world() {
+        let w = World::new();
+
+        assert_eq!(0, w.objects.len());
+        assert_eq!(0, w.lights.len());
+        assert_eq!(5, w.max_depth);
+    }
+
+    #[test]
+    fn the_default_world() {
+        let w = World::default();
+
+        let light = PointLight {
+            position: Tuple::point(-10.0, 10.0, -10.0),
+            intensity: Color::new(1.0, 1.0, 1.0),
+        };
+        let s1_material = Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            diffuse: 0.7,
+            specular: 0.2,
+            ..Material::default()
+        };
+
+        assert_eq!(vec!(light), w.lights);
+        assert_eq!(2, w.objects.len());
+        assert_eq!(&s1_material, w.objects[0].material());
+        assert_eq!(Matrix4::scaling(0.5, 0.5, 0.5), w.objects[1].transform());
+    }
+
+    #[test]
+    fn intersect_a_world_with_a_ray() {
+        let w = World::default();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let xs = w.intersect_world(&r);
+
+        assert_eq!(4, xs.len());
+        assert_eq!(4.0, xs[0].t);
+        assert_eq!(4.5, xs[1].t);
+        assert_eq!(5.5, xs[2].t);
+        assert_eq!(6.0, xs[3].t);
+    }
+
+    #[test]
+    fn shading_an_intersection() {
+        let w = World::default();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let i = Intersection::new(4.0, w.objects[0].as_ref());
+
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        let c = w.shade_hit(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), c.round());
+    }
+
+    #[test]
+    fn shading_an_intersection_from_the_inside() {
+        let mut w = World::default();
+        w.lights[0] = PointLight {
+            position: Tuple::point(0.0, 0.25, 0.0),
+            intensity: Color::new(1.0, 1.0, 1.0),
+        };
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let i = Intersection::new(0.5, w.objects[1].as_ref());
+
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        let c = w.shade_hit(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.90498, 0.90498, 0.90498), c.round());
+    }
+
+    #[test]
+    fn shading_with_multiple_lights_adds_their_contributions() {
+        let mut w = World::default();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let i = Intersection::new(4.0, w.objects[0].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+        let single_light = w.shade_hit(&comps, w.max_depth);
+
+        let light = w.lights[0];
+        w.lights.push(light);
+
+        let c = w.shade_hit(&comps, w.max_depth);
+
+        assert_eq!((single_light * 2.0).round(), c.round());
+    }
+
+    #[test]
+    fn the_color_when_a_ray_misses() {
+        let w = World::default();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 1.0, 0.0),
+        };
+
+        let c = w.color_at(&r);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), c);
+    }
+
+    #[test]
+    fn the_color_when_a_ray_hits() {
+        let w = World::default();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+
+        let c = w.color_at(&r);
+
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), c.round());
+    }
+
+    #[test]
+    fn the_color_with_an_intersection_behind_the_ray() {
+        let mut w = World::default();
+        for object in w.objects.iter_mut() {
+            let material = Material { ambient: 1.0, ..object.material().clone() };
+            object.set_material(material);
+        }
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.75),
+            direction: Tuple::vector(0.0, 0.0, -1.0),
+        };
+
+        let c = w.color_at(&r);
+
+        assert_eq!(w.objects[1].material().color, c);
+    }
+
+    #[test]
+    fn a_world_can_hold_any_number_of_objects() {
+        let mut w = World::new();
+        for x in 0..3 {
+            let mut s = Sphere::new();
+            s.set_transform(Matrix4::translation(x as f64 * 3.0, 0.0, 0.0));
+            w.objects.push(Box::new(s));
+        }
+        let r = Ray {
+            origin: Tuple::point(-5.0, 0.0, 0.0),
+            direction: Tuple::vector(1.0, 0.0, 0.0),
+        };
+
+        let xs = w.intersect_world(&r);
+
+        let actual: Vec<f64> = xs.values.into_iter().map(|i| i.t).collect();
+        assert_eq!(vec!(4.0, 6.0, 7.0, 9.0, 10.0, 12.0), actual);
+    }
+
+    macro_rules! is_shadowed_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (point, expected) = $value;
+
+                let w = World::default();
+
+                let actual = w.is_shadowed(point, &w.lights[0]);
+
+                assert_eq!(expected, actual);
+            }
+        )*
+        }
+    }
+
+    is_shadowed_tests! {
+        there_is_no_shadow_when_nothing_is_collinear_with_point_and_light: (Tuple::point(0.0, 10.0, 0.0), false),
+        the_shadow_when_an_object_is_between_the_point_and_the_light: (Tuple::point(10.0, -10.0, 10.0), true),
+        there_is_no_shadow_when_an_object_is_behind_the_light: (Tuple::point(-20.0, 20.0, -20.0), false),
+        there_is_no_shadow_when_an_object_is_behind_the_point: (Tuple::point(-2.0, 2.0, -2.0), false),
+        there_is_no_shadow_when_an_object_is_beside_the_light: (Tuple::point(-10.0, 8.0, -10.0), false),
+    }
+
+    #[test]
+    fn shade_hit_is_given_an_intersection_in_shadow() {
+        let mut w = World::new();
+        w.lights.push(PointLight {
+            position: Tuple::point(0.0, 0.0, -10.0),
+            intensity: Color::new(1.0, 1.0, 1.0),
+        });
+        let s1 = Sphere::new();
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::translation(0.0, 0.0, 10.0));
+        w.objects = vec!(Box::new(s1), Box::new(s2));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let i = Intersection::new(4.0, w.objects[1].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        let c = w.shade_hit(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.1, 0.1, 0.1), c);
+    }
+
+    #[test]
+    fn the_reflected_color_for_a_nonreflective_material() {
+        let mut w = World::default();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let material = Material { ambient: 1.0, ..w.objects[1].material().clone() };
+        w.objects[1].set_material(material);
+        let i = Intersection::new(1.0, w.objects[1].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        let color = w.reflected_color(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), color);
+    }
+
+    fn world_with_reflective_floor() -> World {
+        let mut w = World::default();
+        let mut shape = Plane::new();
+        shape.set_material(Material { reflective: 0.5, ..Material::default() });
+        shape.set_transform(Matrix4::translation(0.0, -1.0, 0.0));
+        w.objects.push(Box::new(shape));
+
+        return w;
+    }
+
+    #[test]
+    fn the_reflected_color_for_a_reflective_material() {
+        let w = world_with_reflective_floor();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -3.0),
+            direction: Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let i = Intersection::new(SQRT_2, w.objects[2].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        let color = w.reflected_color(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.19033, 0.23792, 0.14275), color.round());
+    }
+
+    #[test]
+    fn shade_hit_with_a_reflective_material() {
+        let w = world_with_reflective_floor();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -3.0),
+            direction: Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let i = Intersection::new(SQRT_2, w.objects[2].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        let color = w.shade_hit(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.87676, 0.92434, 0.82917), color.round());
+    }
+
+    #[test]
+    fn color_at_with_mutually_reflective_surfaces() {
+        let mut w = World::new();
+        w.lights.push(PointLight {
+            position: Tuple::point(0.0, 0.0, 0.0),
+            intensity: Color::new(1.0, 1.0, 1.0),
+        });
+        let mut lower = Plane::new();
+        lower.set_material(Material { reflective: 1.0, ..Material::default() });
+        lower.set_transform(Matrix4::translation(0.0, -1.0, 0.0));
+        let mut upper = Plane::new();
+        upper.set_material(Material { reflective: 1.0, ..Material::default() });
+        upper.set_transform(Matrix4::translation(0.0, 1.0, 0.0));
+        w.objects = vec!(Box::new(lower), Box::new(upper));
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.0),
+            direction: Tuple::vector(0.0, 1.0, 0.0),
+        };
+
+        // terminates instead of overflowing the stack
+        let color = w.color_at(&r);
+
+        assert!(color.red > 0.0);
+    }
+
+    #[test]
+    fn the_reflected_color_at_the_maximum_recursive_depth() {
+        let w = world_with_reflective_floor();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -3.0),
+            direction: Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let i = Intersection::new(SQRT_2, w.objects[2].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections { values: vec!(i) });
+
+        let color = w.reflected_color(&comps, 0);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), color);
+    }
+
+    #[test]
+    fn the_recursion_depth_is_configurable() {
+        let mut w = world_with_reflective_floor();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -3.0),
+            direction: Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let reflected = w.color_at(&r);
+
+        w.max_depth = 0;
+
+        let unreflected = w.color_at(&r);
+
+        assert_eq!(Color::new(0.68643, 0.68643, 0.68643), unreflected.round());
+        assert_ne!(reflected, unreflected);
+    }
+
+    #[test]
+    fn the_refracted_color_with_an_opaque_surface() {
+        let w = World::default();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let shape = w.objects[0].as_ref();
+        let xs = Intersections { values: vec!(Intersection::new(4.0, shape), Intersection::new(6.0, shape)) };
+        let comps = xs[0].prepare_computations(&r, &xs);
+
+        let c = w.refracted_color(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), c);
+    }
+
+    fn world_with_glass_first_object() -> World {
+        let mut w = World::default();
+        let material = Material { transparency: 1.0, refractive_index: 1.5, ..w.objects[0].material().clone() };
+        w.objects[0].set_material(material);
+
+        return w;
+    }
+
+    #[test]
+    fn the_refracted_color_at_the_maximum_recursive_depth() {
+        let w = world_with_glass_first_object();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -5.0),
+            direction: Tuple::vector(0.0, 0.0, 1.0),
+        };
+        let shape = w.objects[0].as_ref();
+        let xs = Intersections { values: vec!(Intersection::new(4.0, shape), Intersection::new(6.0, shape)) };
+        let comps = xs[0].prepare_computations(&r, &xs);
+
+        let c = w.refracted_color(&comps, 0);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), c);
+    }
+
+    #[test]
+    fn the_refracted_color_under_total_internal_reflection() {
+        let w = world_with_glass_first_object();
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, FRAC_1_SQRT_2),
+            direction: Tuple::vector(0.0, 1.0, 0.0),
+        };
+        let shape = w.objects[0].as_ref();
+        let xs = Intersections {
+            values: vec!(Intersection::new(-FRAC_1_SQRT_2, shape), Intersection::new(FRAC_1_SQRT_2, shape))
+        };
+        // inside the sphere, so look at the second intersection
+        let comps = xs[1].prepare_computations(&r, &xs);
+
+        let c = w.refracted_color(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.0, 0.0, 0.0), c);
+    }
+
+    #[test]
+    fn the_refracted_color_with_a_refracted_ray() {
+        let mut w = World::default();
+        let material = Material {
+            ambient: 1.0,
+            pattern: Some(Rc::new(TestPattern::new())),
+            ..w.objects[0].material().clone()
+        };
+        w.objects[0].set_material(material);
+        let material = Material { transparency: 1.0, refractive_index: 1.5, ..w.objects[1].material().clone() };
+        w.objects[1].set_material(material);
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, 0.1),
+            direction: Tuple::vector(0.0, 1.0, 0.0),
+        };
+        let a = w.objects[0].as_ref();
+        let b = w.objects[1].as_ref();
+        let xs = Intersections {
+            values: vec!(
+                Intersection::new(-0.9899, a),
+                Intersection::new(-0.4899, b),
+                Intersection::new(0.4899, b),
+                Intersection::new(0.9899, a),
+            )
+        };
+        let comps = xs[2].prepare_computations(&r, &xs);
+
+        let c = w.refracted_color(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.0, 0.99887, 0.04722), c.round());
+    }
+
+    fn world_with_glass_floor_and_ball(floor_material: Material) -> World {
+        let mut w = World::default();
+        let mut floor = Plane::new();
+        floor.set_transform(Matrix4::translation(0.0, -1.0, 0.0));
+        floor.set_material(floor_material);
+        let mut ball = Sphere::new();
+        ball.set_material(Material {
+            color: Color::new(1.0, 0.0, 0.0),
+            ambient: 0.5,
+            ..Material::default()
+        });
+        ball.set_transform(Matrix4::translation(0.0, -3.5, -0.5));
+        w.objects.push(Box::new(floor));
+        w.objects.push(Box::new(ball));
+
+        return w;
+    }
+
+    #[test]
+    fn shade_hit_with_a_transparent_material() {
+        let w = world_with_glass_floor_and_ball(Material {
+            transparency: 0.5,
+            refractive_index: 1.5,
+            ..Material::default()
+        });
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -3.0),
+            direction: Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let xs = Intersections { values: vec!(Intersection::new(SQRT_2, w.objects[2].as_ref())) };
+        let comps = xs[0].prepare_computations(&r, &xs);
+
+        let color = w.shade_hit(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.93643, 0.68643, 0.68643), color.round());
+    }
+
+    #[test]
+    fn shade_hit_with_a_reflective_transparent_material() {
+        let w = world_with_glass_floor_and_ball(Material {
+            reflective: 0.5,
+            transparency: 0.5,
+            refractive_index: 1.5,
+            ..Material::default()
+        });
+        let r = Ray {
+            origin: Tuple::point(0.0, 0.0, -3.0),
+            direction: Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let xs = Intersections { values: vec!(Intersection::new(SQRT_2, w.objects[2].as_ref())) };
+        let comps = xs[0].prepare_computations(&r, &xs);
+
+        let color = w.shade_hit(&comps, w.max_depth);
+
+        assert_eq!(Color::new(0.93391, 0.69643, 0.69243), color.round());
+    }
+}